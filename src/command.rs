@@ -2,4 +2,7 @@ pub mod runner;
 pub mod git;
 pub mod gitv2;
 pub mod args;
-pub mod browser;
\ No newline at end of file
+pub mod browser;
+pub mod http;
+pub mod jira;
+pub mod gitlab;
\ No newline at end of file
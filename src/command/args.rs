@@ -1,22 +1,81 @@
 use crate::config::store::Store;
+use crate::config::constants::{MAIN_BRANCH_NAME_KEY, TOP_LEVEL_ACTIONS};
 use crate::flow::branch::Branch;
 use crate::flow::init::Script;
+use crate::flow::custom::CustomBranch;
+use crate::flow::version::{Version, Bump};
+use crate::flow::manifest::VersionFile;
+use crate::flow::finish::{Finish, FinishOptions};
+use crate::flow::preflight::{Preflight, PreflightError};
+use crate::flow::sync::Synchronization;
+use crate::flow::status::Status;
+use crate::flow::list::{Listing, ListOptions};
+use crate::flow::cleanup::{Cleanup, CleanupOptions};
+use crate::flow::rename::Rename;
+use crate::flow::switch::Switch;
+use crate::flow::hook::{Hook, HookTime};
+use crate::flow::githooks::GitHooks;
+use crate::flow::policy::NamingPolicy;
+use crate::flow::lint::Lint;
+use crate::flow::tracker::{Issue, IssueTracker};
 
 use crate::command::gitv2::GitV2;
 
-use crate::config::constants::DEVELOP_BRANCH_NAME_KEY;
 use crate::info;
+use crate::warning;
 use crate::working;
 use crate::success;
 use crate::error;
 
-use super::browser::Browser;
+
+/// Options followed by a value, like `--base support/1.x`
+const VALUED_OPTIONS: [&str; 5] = ["--base", "--bump", "--issue", "--stale", "--format"];
+
+///
+/// Flags and options of `git lab <type> start`.
+///
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct StartOptions {
+    /// Where the branch starts from, instead of the default base
+    pub base: Option<String>,
+    /// Stashes the uncommitted changes during the start
+    pub autostash: bool,
+    /// The issue the branch is started from (`--issue ABC-123`)
+    pub issue: Option<Issue>,
+}
 
 #[derive(Debug)]
 pub enum Action {
     Init,
-    Start(Branch, Option<Branch>),
-    Finish(Branch),
+    Start(Branch, StartOptions),
+    Finish(Branch, FinishOptions),
+    /// Continues a finish stopped on conflicts
+    Continue(FinishOptions),
+    /// Gives up a finish stopped on conflicts
+    Abort,
+    /// Finishes the branches whose merge requests were merged
+    /// (keeping their remote branches, or not)
+    Sync(bool),
+    /// Installs the git hooks protecting main and develop
+    InstallHooks,
+    UninstallHooks,
+    /// Runs the given git hook, with its arguments (called by the installed hooks)
+    RunHook(String, Vec<String>),
+    /// Checks the commits of the given branch (or the current one)
+    /// against the Conventional Commits rules
+    Lint(Option<String>),
+    /// Shows the flow branches in progress and how main and develop are
+    Status,
+    /// Lists the branches of the given type
+    List(String, ListOptions),
+    /// Removes the merged (and, optionally, the stale) flow branches
+    Cleanup(CleanupOptions),
+    /// Renames the branch (the first) to the second, locally and on
+    /// the remote, even with a merge request open (when forced)
+    Rename(Branch, Branch, bool),
+    /// Checks out the flow branch (of the given type, or any) whose
+    /// name matches the query
+    Checkout(Option<String>, String),
     // TODO: Publish(Branch),
     // TODO: Pull(Branch),
     // TODO: Track(Branch),    
@@ -24,37 +83,155 @@ pub enum Action {
 
 impl Action {
 
-    fn branch_prefix (args: &Vec<String>) -> Option<&str> {
+    ///
+    /// The arguments that aren't options (nor option values).
+    /// 
+    fn positionals (args: &[String]) -> Vec<&str> {
+
+        let mut positionals: Vec<&str> = Vec::new();
+        let mut is_option_value = false;
+
+        for arg in args.iter() {
+
+            if is_option_value {
+                is_option_value = false;
+                continue;
+            }
+
+            if arg.starts_with("--") {
+                is_option_value = VALUED_OPTIONS.contains(&arg.as_str());
+                continue;
+            }
+
+            positionals.push(arg);
+
+        }
+
+        positionals
+
+    }
+
+    ///
+    /// The value of the given option (`--base support/1.x`), if informed.
+    /// 
+    fn option<'a> (args: &'a [String], name: &str) -> Option<&'a str> {
+        args.iter()
+            .position(|arg| arg == name)
+            .and_then(|index| args.get(index + 1))
+            .map(|value| value.as_str())
+    }
+
+    ///
+    /// Tells if the given flag (`--continue`) was informed.
+    /// 
+    fn flag (args: &[String], name: &str) -> bool {
+        args.iter().any(|arg| arg == name)
+    }
+
+    ///
+    /// Tells if the output is meant for scripts (`--format json`),
+    /// or if it's a git hook running (`hooks run`), so nothing but
+    /// the result (or the errors) is printed.
+    /// 
+    pub fn is_quiet (args: &[String]) -> bool {
+        Self::option(args, "--format") == Some("json") || Self::positionals(args).get(1..3) == Some(&["hooks", "run"])
+    }
 
-        if args.len() >= 2 {
-            return Some(&args[1]);
+    ///
+    /// The days of `--stale <days>`: `Some(None)` when not informed,
+    /// `None` when they aren't a number.
+    /// 
+    fn stale (args: &[String]) -> Option<Option<u32>> {
+        match Self::option(args, "--stale") {
+            Some(days) => match days.parse::<u32>() {
+                Ok(days) => Some(Some(days)),
+                Err(_) => {
+                    error!("--stale needs a number of days, not '{}'.", days);
+                    None
+                }
+            },
+            None => Some(None)
         }
+    }
+
+    fn list_options (args: &[String]) -> Option<ListOptions> {
+
+        let stale = Self::stale(args)?;
 
-        None
+        let json = match Self::option(args, "--format") {
+            Some("json") => true,
+            Some("text") | None => false,
+            Some(format) => {
+                error!("Unknown format '{}': use text or json.", format);
+                return None;
+            }
+        };
+
+        Some(ListOptions {
+            merged: Self::flag(args, "--merged"),
+            stale,
+            mine: Self::flag(args, "--mine"),
+            json,
+        })
 
     }
 
-    fn action_text (args: &Vec<String>) -> Option<&str> {
+    fn cleanup_options (args: &[String]) -> Option<CleanupOptions> {
+
+        let stale = Self::stale(args)?;
+
+        Some(CleanupOptions {
+            yes: Self::flag(args, "--yes"),
+            remote: Self::flag(args, "--remote"),
+            stale,
+        })
+
+    }
 
-        if args.len() >= 2 && args[1] == "init" {
-            return Some(&args[1]);
+    fn finish_options (args: &[String]) -> FinishOptions {
+        FinishOptions {
+            force: Self::flag(args, "--force"),
+            autostash: Self::flag(args, "--autostash"),
+            keep_remote: Self::flag(args, "--keep-remote"),
         }
+    }
+
+    fn branch_prefix (args: &[String]) -> Option<&str> {
+        Self::positionals(args).get(1).copied()
+    }
+
+    fn action_text (args: &[String]) -> Option<&str> {
 
-        if args.len() >= 3 {
-            return Some(&args[2]);
+        let positionals = Self::positionals(args);
+
+        if let Some(&action) = positionals.get(1) {
+            if TOP_LEVEL_ACTIONS.contains(&action) {
+                return Some(action);
+            }
         }
 
-        None
+        positionals.get(2).copied()
 
     }
 
-    fn branch_name (args: &Vec<String>) -> Option<&str> {
-        
-        if args.len() >= 4 {
-            return Some(&args[3]);
+    fn branch_name (args: &[String]) -> Option<&str> {
+        Self::positionals(args).get(3).copied()
+    }
+
+    ///
+    /// Where the new branch starts from: the `--base` option or,
+    /// for support branches, the tag following the branch name.
+    /// 
+    fn base_name (args: &[String]) -> Option<String> {
+
+        if let Some(base) = Self::option(args, "--base") {
+            return Some(base.to_string());
         }
 
-        None
+        match Self::branch_prefix(args) {
+            Some("support") => Self::positionals(args).get(4).map(|tag| tag.to_string()),
+            _ => None
+        }
 
     }
 
@@ -65,11 +242,104 @@ impl Action {
             "hotfix" => Some(Branch::Hotfix(name.to_string())),
             "bugfix" => Some(Branch::Bugfix(name.to_string())),
             "release" => Some(Branch::Release(name.to_string())),
-            _ => None
+            "support" => Some(Branch::Support(name.to_string())),
+            // Anything else may be a branch type declared by the user
+            custom_type => match CustomBranch::load(custom_type) {
+                Ok(custom) => Some(Branch::Custom(custom.name, name.to_string())),
+                Err(_) => None
+            }
+        }
+
+    }
+
+    ///
+    /// Names the release (or hotfix) after the version following
+    /// the latest one tagged on the base (main, by default).
+    /// Hotfixes without a name get the next patch version.
+    /// 
+    fn bumped_branch (args: &[String]) -> Option<Branch> {
+
+        let branch_prefix = Self::branch_prefix(args)?;
+
+        let bump = match (Self::option(args, "--bump"), Self::branch_name(args)) {
+            (Some(_), Some(name)) => {
+                error!("--bump names the branch after the next version: give either the name '{}' or --bump, not both.", name);
+                return None;
+            },
+            (Some(bump_text), None) => match Bump::parse(bump_text) {
+                Some(bump) => bump,
+                None => {
+                    error!("Unknown version bump '{}': use major, minor, patch or pre.", bump_text);
+                    return None;
+                }
+            },
+            (None, None) if branch_prefix == "hotfix" => Bump::Patch,
+            _ => return None
+        };
+
+        if branch_prefix != "release" && branch_prefix != "hotfix" {
+            error!("Only releases and hotfixes are named after versions.");
+            return None;
+        }
+
+        let base_name = match Self::option(args, "--base") {
+            Some(base) => base.to_string(),
+            None => match Store::get(MAIN_BRANCH_NAME_KEY) {
+                Ok(main_name) => String::from(main_name.trim()),
+                Err(e) => {
+                    error!("{}", e);
+                    return None;
+                }
+            }
+        };
+
+        match Version::next(&base_name, bump) {
+            Ok(version) => {
+                info!("The next version after {} is {}", base_name, version);
+                Self::branch(branch_prefix, &version.to_string())
+            },
+            Err(e) => {
+                error!("Couldn't find the latest version on {}: {}", base_name, e);
+                None
+            }
         }
 
     }
 
+    ///
+    /// Names the branch after the issue given with `--issue`
+    /// (unless a name is given too), following the naming policy.
+    /// 
+    fn issue_branch (args: &[String]) -> Option<(Branch, Issue)> {
+
+        let branch_prefix = Self::branch_prefix(args)?;
+        let issue_key = Self::option(args, "--issue")?;
+
+        let issue = match Issue::fetch(issue_key) {
+            Ok(issue) => issue,
+            Err(e) => {
+                error!("Couldn't fetch issue {}: {}", issue_key, e.trim());
+                return None;
+            }
+        };
+
+        info!("Issue {}: {}", &issue.key, &issue.title);
+
+        let name = match Self::branch_name(args) {
+            Some(name) => name.to_string(),
+            None => match NamingPolicy::load(branch_prefix).name_for_issue(&issue.key, &issue.title) {
+                Ok(name) => name,
+                Err(e) => {
+                    error!("Couldn't name the branch after issue {}: {}\nGive it a name: `git lab {} start NAME --issue {}`.", &issue.key, e, branch_prefix, &issue.key);
+                    return None;
+                }
+            }
+        };
+
+        Self::branch(branch_prefix, &name).map(|branch| (branch, issue))
+
+    }
+
     fn calculate_branch (args: &Vec<String>) -> Option<Branch> {
 
         let branch_prefix = Self::branch_prefix(args);
@@ -121,11 +391,67 @@ impl Action {
             // git flow init ...
             Some("init") => Some(Action::Init),
 
+            // git flow hooks install | uninstall | run <hook>
+            Some("hooks") => match Self::positionals(args).get(2).copied() {
+                Some("install") => Some(Action::InstallHooks),
+                Some("uninstall") => Some(Action::UninstallHooks),
+                Some("run") => Self::positionals(args).get(3).map(|hook_name| Action::RunHook(
+                    hook_name.to_string(),
+                    Self::positionals(args).iter().skip(4).map(|arg| arg.to_string()).collect()
+                )),
+                _ => None
+            },
+
+            // git flow lint [<branch>]
+            Some("lint") => Some(Action::Lint(Self::positionals(args).get(2).map(|branch| branch.to_string()))),
+
+            // git flow status
+            Some("status") => Some(Action::Status),
+
+            // git flow <action> list [--merged] [--stale <days>] [--mine] [--format text|json]
+            Some("list") => {
+
+                let type_name = Self::branch_prefix(args).and_then(|prefix| Self::branch(prefix, "")).map(|branch| branch.type_name())?;
+
+                Self::list_options(args).map(|options| Action::List(type_name, options))
+
+            },
+
+            // git flow cleanup [--yes] [--remote] [--stale <days>]
+            Some("cleanup") => Self::cleanup_options(args).map(Action::Cleanup),
+
+            // git flow <action> rename <old> <new> [--force]
+            Some("rename") => {
+
+                let branch_prefix = Self::branch_prefix(args)?;
+                let new_name = Self::positionals(args).get(4).copied()?;
+
+                match (Self::calculate_branch(args), Self::branch(branch_prefix, new_name)) {
+                    (Some(branch), Some(renamed)) => Some(Action::Rename(branch, renamed, Self::flag(args, "--force"))),
+                    _ => None
+                }
+
+            },
+
+            // git flow [<action>] checkout <query>
+            Some("checkout") => match Self::positionals(args).as_slice() {
+                [_, "checkout", query, ..] => Some(Action::Checkout(None, query.to_string())),
+                [_, prefix, "checkout", query, ..] => Self::branch(prefix, "").map(|branch| Action::Checkout(Some(branch.type_name()), query.to_string())),
+                _ => None
+            },
+
+            // git flow sync [--keep-remote]
+            Some("sync") => Some(Action::Sync(Self::flag(args, "--keep-remote"))),
+
+            // git flow [<action>] finish --continue | --abort
+            Some("finish") if Self::flag(args, "--continue") => Some(Action::Continue(Self::finish_options(args))),
+            Some("finish") if Self::flag(args, "--abort") => Some(Action::Abort),
+
             // git flow <action> finish <branch> ...
             Some("finish") => {
 
                 match Self::calculate_branch(&args) {
-                    Some(branch) => Some(Action::Finish(branch)),
+                    Some(branch) => Some(Action::Finish(branch, Self::finish_options(args))),
                     None => None
                 }
 
@@ -134,8 +460,21 @@ impl Action {
             // git flow <action> start <branch> ...
             Some("start") => {
 
-                match Self::calculate_branch(&args) {
-                    Some(branch) => Some(Action::Start(branch, None)),
+                let (branch, issue) = match (Self::option(args, "--bump"), Self::option(args, "--issue")) {
+                    (Some(_), _) => (Self::bumped_branch(args), None),
+                    (None, Some(_)) => match Self::issue_branch(args) {
+                        Some((branch, issue)) => (Some(branch), Some(issue)),
+                        None => (None, None)
+                    },
+                    (None, None) => (Self::calculate_branch(args).or_else(|| Self::bumped_branch(args)), None)
+                };
+
+                match branch {
+                    Some(branch) => Some(Action::Start(branch, StartOptions {
+                        base: Self::base_name(args),
+                        autostash: Self::flag(args, "--autostash"),
+                        issue,
+                    })),
                     None => None
                 }
 
@@ -168,10 +507,91 @@ impl Action {
 
     }
 
-    fn start (branch: &Branch, _source_branch: &Option<Branch>) -> Result<(), String> {
+    ///
+    /// Fetches the base (a branch or a tag) from the remote, if
+    /// there is one. Tells how many commits the local branch is
+    /// behind, to be fast-forwarded, and refuses a base that
+    /// diverged from the remote. Being offline is not an error:
+    /// the local base is used.
+    /// 
+    fn fetch_base (base_name: &str) -> Result<u32, String> {
+
+        if !GitV2::is_remote() {
+            return Ok(0);
+        }
+
+        let refspec = match GitV2::is_tag(base_name) {
+            true => format!("tag {}", base_name),
+            false => base_name.to_string()
+        };
+
+        if let Err(e) = GitV2::fetch(&refspec) {
+            warning!("Couldn't fetch {} from the remote, starting from the local one: {}", base_name, e.trim());
+            return Ok(0);
+        }
+
+        let remote_base = format!("origin/{}", base_name);
+
+        // Tags don't move, and a local-only branch has nothing to catch up with
+        if GitV2::is_tag(base_name) || GitV2::rev_parse(&remote_base).is_err() || GitV2::rev_parse(base_name).is_err() {
+            return Ok(0);
+        }
+
+        let ahead = GitV2::count_commits(&remote_base, base_name)?;
+        let behind = GitV2::count_commits(base_name, &remote_base)?;
+
+        if ahead > 0 && behind > 0 {
+            return Err(format!(
+                "{} has diverged from {} ({} local and {} remote commit(s)): reconcile them before starting a branch.",
+                base_name,
+                &remote_base,
+                ahead,
+                behind
+            ));
+        }
+
+        Ok(behind)
+
+    }
+
+    ///
+    /// Goes back to the base, throwing away what the start changed,
+    /// and removes the branch it created.
+    ///
+    fn undo_start (prefix: Option<&str>, branch_name: &str, base_name: &str) {
+
+        let undone = GitV2::checkout_discarding(base_name)
+            .and_then(|_| GitV2::remove_local_branch(prefix, branch_name, true));
+
+        match undone {
+            Ok(_) => info!("Branch {}{} removed: start it again once the version files are fixed", prefix.unwrap_or(""), branch_name),
+            Err(e) => error!("Couldn't undo the start of {}{}: {}", prefix.unwrap_or(""), branch_name, e)
+        }
+
+    }
+
+    fn start (branch: &Branch, options: &StartOptions) -> Result<(), String> {
+
+        let base = &options.base;
 
         working!("Starting branch {}", branch.name());
 
+        // Releases are named after their version
+        if let Branch::Release(name) = branch {
+            if Version::parse(name).is_none() {
+                return Err(format!(
+                    "The release name '{}' is not a semantic version (like 1.4.0). Use --bump to compute it.",
+                    name
+                ));
+            }
+        }
+
+        // O nome segue a política do tipo de branch?
+        match NamingPolicy::load(&branch.type_name()).check(branch.name()) {
+            Ok(_) => {},
+            Err(e) => { return Err(e); }
+        }
+
         let mut prefix_text: String = String::new();
 
         match branch.prefix() {
@@ -188,20 +608,44 @@ impl Action {
 
         working!("Prefix is {:?}", &prefix);
 
-        let develop_name = match Store::get(DEVELOP_BRANCH_NAME_KEY) {
-            Ok(name) => name,
-            Err(e) => { return Err(e); }
+        let base_name = match base {
+            Some(name) => name.clone(),
+            None => match branch.base() {
+                Ok(name) => name,
+                Err(e) => { return Err(e); }
+            }
         };
 
-        working!("Develop name is {}", develop_name);
+        working!("Base name is {}", base_name);
+
+        let start_hook = Hook::new("start", branch, &base_name, &[]);
+
+        match start_hook.run(HookTime::Pre) {
+            Ok(_) => {},
+            Err(e) => { return Err(e); }
+        }
+
+        // Buscamos a base no remote, para não começar de código velho
+        let behind = match Self::fetch_base(&base_name) {
+            Ok(behind) => behind,
+            Err(e) => { return Err(e); }
+        };
 
-        // Vamos pra develop...
-        match GitV2::checkout(None, &develop_name, false) {
+        // Vamos pra base (develop, normalmente)...
+        match GitV2::checkout(None, &base_name, false) {
             Ok(_) => {},
             Err(e) => { return Err(e); }
         }
 
-        success!("Checkout to develop done!");
+        success!("Checkout to {} done!", base_name);
+
+        // ... e a atualizamos
+        if behind > 0 {
+            match GitV2::fast_forward(&format!("origin/{}", base_name)) {
+                Ok(_) => { success!("{} moved {} commit(s) forward to origin/{}", base_name, behind, base_name); },
+                Err(e) => { return Err(e); }
+            }
+        }
 
         // Criamos a branch nova...
         match GitV2::checkout(prefix, branch.name(), true) {
@@ -211,120 +655,150 @@ impl Action {
 
         success!("Checkout of branch {} done!", &branch.name());
 
+        // Guardamos a base informada, para sabermos onde terminar a branch
+        if base.is_some() {
+            match branch.record_base(&base_name) {
+                Ok(_) => {},
+                Err(e) => { return Err(e); }
+            }
+        }
+
+        // O merge request vai levar o título e a descrição do ticket
+        if let Some(issue) = &options.issue {
+            match branch.record_merge_request(&issue.merge_request_title(), &issue.merge_request_description()) {
+                Ok(_) => {},
+                Err(e) => { return Err(e); }
+            }
+        }
+
+        // Releases e hotfixes atualizam a versão nos arquivos do projeto
+        if let Branch::Release(name) | Branch::Hotfix(name) = branch {
+            match Version::parse(name) {
+                Some(version) => match VersionFile::bump_all(&version.to_string()) {
+                    Ok(_) => {},
+                    Err(e) => {
+                        // Sem a versão, desfazemos a branch (e os arquivos já reescritos)
+                        Self::undo_start(prefix, branch.name(), &base_name);
+                        return Err(e);
+                    }
+                },
+                None => { info!("{} is not a version: version files left untouched", name); }
+            }
+        }
+
+        let publish_hook = Hook::new("publish", branch, &base_name, &[]);
+
+        match publish_hook.run(HookTime::Pre) {
+            Ok(_) => {},
+            Err(e) => { return Err(e); }
+        }
+
         // Damos push caso exista o remote
         match GitV2::push(&format!("{}{}", &prefix_text, &branch.name()), true) {
-            Ok(_) => {},
+            Ok(_) => {
+                success!("Push done!");
+                publish_hook.run(HookTime::Post)?;
+            },
             Err(_) => { error!("Error pushing to remote... Is there a remote server?"); }
         }
 
-        success!("Push done!");
+        // O ticket da branch passa para "In Progress"
+        IssueTracker::started(branch);
 
-        Ok(())
+        start_hook.run(HookTime::Post)
 
     }
 
-    fn finish (branch: &Branch) -> Result<(), String> {
+    ///
+    /// Checks the repository is ready for the action, before
+    /// touching anything. Gives the stash of the uncommitted changes
+    /// (`--autostash`), to be restored afterwards.
+    /// 
+    pub fn preflight (&self) -> Result<Option<String>, PreflightError> {
 
-        let branch_prefix_option_string = match branch.prefix() {
-            Some(pfx) => Some(pfx.clone()),
-            None => None
-        };
+        match self {
 
-        let branch_prefix_str = match &branch_prefix_option_string {
-            Some(pfx) => pfx.as_str(),
-            None => ""
-        };
+            Action::Init | Action::RunHook(_, _) => Ok(None),
 
-        let branch_prefix_option_str = match branch_prefix_str {
-            "" => None,
-            _ => Some(branch_prefix_str)
-        };
+            Action::InstallHooks | Action::UninstallHooks | Action::Lint(_) | Action::Status | Action::List(_, _) => {
+                Preflight::initiated()?;
+                Ok(None)
+            },
 
-        let branch_fullname = format!("{}{}", branch_prefix_str, branch.name());
+            // The base is brought up to date by the start itself
+            Action::Start(_, options) => {
 
-        let branch_sources = match branch.source() {
-            Ok(sources) => sources,
-            Err(e) => { return Err(e); }
-        };
+                Preflight::initiated()?;
+                Preflight::no_operation_in_progress()?;
+                Preflight::attached_head()?;
 
-        // 1. Tem remoto?
-        if GitV2::is_remote() {
+                Preflight::clean_tree(options.autostash)
 
-            // 1.1. Se não tem, criamos branch no remoto. Se tem, atualizamos no remoto            
-            match GitV2::push(&branch_fullname, ! GitV2::exists_remote(&branch_fullname)) {
-                Ok(_) => { success!("Branch {} pushed to remote!", &branch_fullname); },
-                Err(e) => { return Err(e); }
-            }
+            },
 
-            branch_sources.iter().for_each(|source| {
-                match Browser::merge_request(&branch, source) {
-                    Ok(_) => {},
-                    Err(e) => { error!("Something weird while opening merge request 🫣: {}", e); }
-                }
-            });
+            Action::Finish(branch, options) => {
 
-        } else { // Não tem remoto!
-            // 1.3. Se não tem, mergeamos branch nas sources
-            branch_sources.iter().for_each(|target_branch| {
+                Preflight::initiated()?;
+                Preflight::no_operation_in_progress()?;
+                Preflight::attached_head()?;
 
-                // Valor em Option<String> 
-                let target_branch_prefix = match target_branch.prefix() {
-                    Some(pfx) => Some(pfx.clone()),
-                    None => None
-                };
+                for target in branch.source().unwrap_or_default() {
+                    Preflight::base_up_to_date(&target.full_name())?;
+                }
 
-                // Valor como precisamos, Option<&str>
-                let target_branch_prefix = match &target_branch_prefix {
-                    Some(pfx) => Some(pfx.as_str()),
-                    None => None
-                };
+                Preflight::lint(branch)?;
 
-                match GitV2::merge_local(
-                    branch_prefix_option_str,
-                    &branch.name(), 
-                    target_branch_prefix, 
-                    &target_branch.name()
-                ) {
-                    Ok(_) => {},
-                    Err(e) => { error!("Something weird while merging local branches 🫣: {}", e); }
-                }
-            });
-        }
+                Preflight::clean_tree(options.autostash)
 
-            
-        // 1.2. Vamos para a develop...
-        match Store::get(DEVELOP_BRANCH_NAME_KEY) {
-            Ok(develop_name) => {
-                match GitV2::checkout(None, &develop_name, false) {
-                    Ok(_) => {},
-                    Err(e) => { return Err(e); }
-                }
             },
-            Err(e) => { return Err(e); }
-        }
 
-        // 1.3. ... e agora podemos remover a branch local (já estamos na develop)
-        match GitV2::remove_local_branch(branch_prefix_option_str, &branch.name()) {
-            Ok(_) => { success!("Branch {} removed from local", &branch_fullname); },
-            Err(e) => { return Err(e); }
-        }
-            
+            // Merged branches may be checked out, to be left
+            Action::Sync(_) | Action::Cleanup(_) => {
 
-        // 2. Checkout da develop
+                Preflight::initiated()?;
+                Preflight::no_operation_in_progress()?;
 
-        // 3. Se deu certo, removemos a branch local
+                Preflight::clean_tree(false)
 
-        Ok(())
+            },
+
+            // The checked out branch may be renamed too, and git
+            // itself refuses to switch over conflicting changes
+            Action::Rename(_, _, _) | Action::Checkout(_, _) => {
+                Preflight::initiated()?;
+                Preflight::no_operation_in_progress()?;
+                Ok(None)
+            },
+
+            // The merge stopped on conflicts is still in progress
+            Action::Continue(_) | Action::Abort => {
+                Preflight::initiated()?;
+                Ok(None)
+            },
+
+        }
 
     }
-    
+
     pub fn execute (&self) -> Result<(), String> {
 
         match self {
 
             Action::Init => Self::init(),
-            Action::Start(branch, source) => Self::start(branch, source),
-            Action::Finish(branch) => Self::finish(branch),
+            Action::Start(branch, options) => Self::start(branch, options),
+            Action::Finish(branch, options) => Finish::run(branch, *options),
+            Action::Continue(options) => Finish::resume(*options),
+            Action::Abort => Finish::abort(),
+            Action::Sync(keep_remote) => Synchronization::run(*keep_remote),
+            Action::InstallHooks => GitHooks::install(),
+            Action::UninstallHooks => GitHooks::uninstall(),
+            Action::RunHook(hook_name, hook_args) => GitHooks::run(hook_name, hook_args),
+            Action::Lint(branch_fullname) => Lint::run(branch_fullname.as_deref()),
+            Action::Status => Status::run(),
+            Action::List(type_name, options) => Listing::run(type_name, options),
+            Action::Cleanup(options) => Cleanup::run(options),
+            Action::Rename(branch, renamed, force) => Rename::run(branch, renamed, *force),
+            Action::Checkout(type_name, query) => Switch::run(type_name.as_deref(), query),
 
         }
 
@@ -9,7 +9,13 @@ pub enum Browser {}
 
 impl Browser {
 
-    pub fn merge_request (branch: &Branch, origin: &Branch) -> Result<String, String> {
+    ///
+    /// Opens the GitLab page of a new merge request, from the
+    /// given branch into the origin, with an optional description.
+    /// The title and description recorded for the branch (from
+    /// its issue) are used when there are some.
+    /// 
+    pub fn merge_request (branch: &Branch, origin: &Branch, description: Option<&str>) -> Result<String, String> {
 
         return match GitV2::remote_push_url() {
 
@@ -31,7 +37,12 @@ impl Browser {
 
                 let origin_name = encode(&origin_name);
 
-                let merge_request_message = format!("Merging branch {} into {}", branch_name, origin_name);
+                let (recorded_title, recorded_description) = branch.recorded_merge_request();
+
+                let merge_request_message = match recorded_title {
+                    Some(title) => title,
+                    None => format!("Merging branch {} into {}", branch_name, origin_name)
+                };
                 let merge_request_message = encode(&merge_request_message);
 
                 let url = format!(
@@ -42,6 +53,14 @@ impl Browser {
                     merge_request_message
                 );
 
+                // The issue goes first, then the changelog (releases)
+                let description: Vec<&str> = recorded_description.as_deref().into_iter().chain(description).collect();
+
+                let url = match description.is_empty() {
+                    false => format!("{}&merge_request[description]={}", url, encode(&description.join("\n\n"))),
+                    true => url
+                };
+
                 match Runner::open(&url) {
                     Ok(_) => Ok(url),
                     Err(err) => Err(err)
@@ -1,5 +1,6 @@
 use regex::Regex;
 use crate::{command::runner::{Runner}, config::constants::COMMIT_HASH_REGEX_PATTERN, info, working, error};
+use crate::flow::strategy::MergeStrategy;
 
 /**
  * git-flow vs git (Very cool comparison)
@@ -13,6 +14,42 @@ use crate::{command::runner::{Runner}, config::constants::COMMIT_HASH_REGEX_PATT
 /// 
 pub enum GitV2 {}
 
+///
+/// A commit, as read from `git log`
+/// 
+#[derive(Debug, PartialEq, Clone)]
+pub struct Commit {
+    pub hash: String,
+    pub parents: Vec<String>,
+    pub subject: String,
+    pub body: String,
+}
+
+impl Commit {
+
+    pub fn is_merge (&self) -> bool {
+        self.parents.len() > 1
+    }
+
+    pub fn short_hash (&self) -> &str {
+        &self.hash[..self.hash.len().min(7)]
+    }
+
+}
+
+///
+/// Who made a commit, and when.
+///
+#[derive(Debug, PartialEq, Clone)]
+pub struct Signature {
+    /// Seconds since the epoch
+    pub timestamp: i64,
+    /// ISO 8601
+    pub date: String,
+    pub author: String,
+    pub email: String,
+}
+
 ///
 /// This is the GitV2 default implementation
 /// 
@@ -70,6 +107,7 @@ impl GitV2 {
         }
     }
 
+    #[allow(dead_code)]
     pub fn exists_remote (branch_fullname: &str) -> bool {
         match Runner::run(&format!("git ls-remote --heads origin {}", branch_fullname)) {
             Ok(remote_response) => {
@@ -118,6 +156,64 @@ impl GitV2 {
         }
     }
 
+    ///
+    /// Returns the commit the branch points to on the remote, if it exists there
+    /// 
+    pub fn remote_commit (branch_fullname: &str) -> Option<String> {
+        match Runner::run(&format!("git ls-remote --heads origin refs/heads/{}", branch_fullname)) {
+            Ok(output) => output
+                .split_whitespace()
+                .next()
+                .map(|commit| commit.to_string()),
+            Err(_) => None
+        }
+    }
+
+    ///
+    /// Points the remote branch to the given commit, even backwards,
+    /// unless someone else pushed to it meanwhile
+    /// 
+    pub fn push_commit (commit: &str, branch_fullname: &str) -> Result<String, String> {
+        Runner::run(&format!("git push --force-with-lease origin {}:refs/heads/{}", commit, branch_fullname))
+    }
+
+    ///
+    /// Removes the branch from the remote repository
+    /// 
+    pub fn remove_remote_branch (branch_fullname: &str) -> Result<String, String> {
+        Runner::run(&format!("git push origin --delete {}", branch_fullname))
+    }
+
+    ///
+    /// Fetches the given branch (or `tag NAME`) from the remote repository
+    /// 
+    pub fn fetch (refspec: &str) -> Result<String, String> {
+        Runner::run(&format!("git fetch origin {}", refspec))
+    }
+
+    ///
+    /// Fetches every branch from the remote repository, removing the
+    /// remote-tracking branches deleted there
+    /// 
+    pub fn fetch_prune () -> Result<String, String> {
+        Runner::run("git fetch --prune origin")
+    }
+
+    ///
+    /// Checks if the given name is a tag
+    /// 
+    pub fn is_tag (name: &str) -> bool {
+        Runner::run(&format!("git rev-parse --verify -q refs/tags/{}", name)).is_ok()
+    }
+
+    ///
+    /// Moves the checked out branch forward to the given commit,
+    /// failing if that's not a fast-forward
+    /// 
+    pub fn fast_forward (commit: &str) -> Result<String, String> {
+        Runner::run(&format!("git merge --ff-only {}", commit))
+    }
+
     ///
     /// Retrieve changes from the remote repository
     ///
@@ -126,6 +222,210 @@ impl GitV2 {
         Runner::run(&format!("git pull origin {}", branch_name))
     }
 
+    ///
+    /// Returns the top level directory of the current git repository
+    /// 
+    pub fn root_dir () -> Result<String, String> {
+        match Runner::run("git rev-parse --show-toplevel") {
+            Ok(output) => Ok(output.trim().to_string()),
+            Err(e) => Err(e)
+        }
+    }
+
+    ///
+    /// Returns the .git directory of the current git repository
+    /// 
+    pub fn git_dir () -> Result<String, String> {
+        match Runner::run("git rev-parse --absolute-git-dir") {
+            Ok(output) => Ok(output.trim().to_string()),
+            Err(e) => Err(e)
+        }
+    }
+
+    ///
+    /// Returns the directory git runs the hooks from (core.hooksPath or .git/hooks)
+    /// 
+    pub fn hooks_dir () -> Result<String, String> {
+        match Runner::run("git rev-parse --git-path hooks") {
+            Ok(output) => Ok(output.trim().to_string()),
+            Err(e) => Err(e)
+        }
+    }
+
+    ///
+    /// Returns the commit hash the given reference points to
+    /// 
+    pub fn rev_parse (reference: &str) -> Result<String, String> {
+        match Runner::run(&format!("git rev-parse --verify -q {}^{{commit}}", reference)) {
+            Ok(output) => Ok(output.trim().to_string()),
+            Err(_) => Err(format!("Unknown reference '{}'", reference))
+        }
+    }
+
+    ///
+    /// Returns the name of the checked out branch (None when detached)
+    /// 
+    pub fn current_branch () -> Option<String> {
+        match Runner::run("git symbolic-ref --short -q HEAD") {
+            Ok(output) if !output.trim().is_empty() => Some(output.trim().to_string()),
+            _ => None
+        }
+    }
+
+    ///
+    /// Returns the files with unresolved merge conflicts
+    /// 
+    pub fn unmerged_files () -> Result<Vec<String>, String> {
+        match Runner::run("git diff --name-only --diff-filter=U") {
+            Ok(output) => Ok(
+                output
+                    .lines()
+                    .map(|line| line.trim().to_string())
+                    .filter(|line| !line.is_empty())
+                    .collect()
+            ),
+            Err(e) => Err(e)
+        }
+    }
+
+    ///
+    /// Checks if a merge is waiting to be concluded (MERGE_HEAD exists)
+    /// 
+    pub fn is_merging () -> bool {
+        match GitV2::git_dir() {
+            Ok(git_dir) => std::path::Path::new(&git_dir).join("MERGE_HEAD").exists(),
+            Err(_) => false
+        }
+    }
+
+    ///
+    /// Checks if a rebase is waiting to be concluded
+    /// 
+    pub fn is_rebasing () -> bool {
+        match GitV2::git_dir() {
+            Ok(git_dir) => {
+                let git_dir = std::path::Path::new(&git_dir);
+                git_dir.join("rebase-merge").exists() || git_dir.join("rebase-apply").exists()
+            },
+            Err(_) => false
+        }
+    }
+
+    ///
+    /// Checks if a cherry-pick or a revert is waiting to be concluded
+    /// 
+    pub fn is_picking () -> bool {
+        match GitV2::git_dir() {
+            Ok(git_dir) => {
+                let git_dir = std::path::Path::new(&git_dir);
+                git_dir.join("CHERRY_PICK_HEAD").exists() || git_dir.join("REVERT_HEAD").exists()
+            },
+            Err(_) => false
+        }
+    }
+
+    ///
+    /// Returns the tracked files with uncommitted changes (staged or not)
+    /// 
+    pub fn changed_files () -> Result<Vec<String>, String> {
+        match Runner::run("git status --porcelain --untracked-files=no") {
+            Ok(output) => Ok(
+                output
+                    .lines()
+                    .filter_map(|line| line.get(3..))
+                    .map(|file| file.trim().to_string())
+                    .filter(|file| !file.is_empty())
+                    .collect()
+            ),
+            Err(e) => Err(e)
+        }
+    }
+
+    ///
+    /// Returns how many commits the second reference has that the first hasn't
+    /// 
+    pub fn count_commits (from: &str, to: &str) -> Result<u32, String> {
+        match Runner::run(&format!("git rev-list --count {}..{}", from, to)) {
+            Ok(output) => match output.trim().parse::<u32>() {
+                Ok(count) => Ok(count),
+                Err(e) => Err(e.to_string())
+            },
+            Err(e) => Err(e)
+        }
+    }
+
+    ///
+    /// Stashes the uncommitted changes of the tracked files
+    /// 
+    pub fn stash (message: &str) -> Result<String, String> {
+        Runner::run(&format!("git stash push -m {}", Runner::quote(message)))
+    }
+
+    ///
+    /// Applies the given stash (its commit) and removes it
+    /// 
+    pub fn stash_pop (stash: &str) -> Result<String, String> {
+
+        let stashes = Runner::run("git stash list --format=%H")?;
+
+        match stashes.lines().position(|commit| commit.trim() == stash) {
+            Some(index) => Runner::run(&format!("git stash pop stash@{{{}}}", index)),
+            None => Err(format!("There is no stash {} anymore", stash))
+        }
+
+    }
+
+    ///
+    /// Checks if there are staged changes waiting to be committed
+    /// 
+    pub fn has_staged_changes () -> bool {
+        Runner::run("git diff --cached --quiet").is_err()
+    }
+
+    ///
+    /// Points the given reference (refs/heads/develop) to the given commit
+    /// 
+    pub fn update_ref (reference: &str, commit: &str) -> Result<String, String> {
+        Runner::run(&format!("git update-ref {} {}", reference, commit))
+    }
+
+    ///
+    /// Checks if the given commit is reachable from (contained in) the other one
+    /// 
+    pub fn is_ancestor (commit: &str, descendant: &str) -> bool {
+        Runner::run(&format!("git merge-base --is-ancestor {} {}", commit, descendant)).is_ok()
+    }
+
+    ///
+    /// Checks if the given commit is in the first-parent history of the
+    /// branch: it was committed (or fast-forwarded) there, not merged in
+    /// 
+    pub fn is_on_first_parent (commit: &str, branch: &str) -> bool {
+        match Runner::run(&format!("git rev-list --first-parent {}", branch)) {
+            Ok(output) => output.lines().any(|line| line.trim() == commit),
+            Err(_) => false
+        }
+    }
+
+    ///
+    /// Checks if the changes of the source are already in the target,
+    /// even if its commits aren't (squashed, for instance): merging
+    /// it would leave the target tree as it is.
+    /// 
+    pub fn is_content_merged (source: &str, target: &str) -> bool {
+
+        let merged_tree = match Runner::run(&format!("git merge-tree --write-tree {} {}", target, source)) {
+            Ok(output) => output.lines().next().unwrap_or_default().trim().to_string(),
+            Err(_) => return false
+        };
+
+        match Runner::run(&format!("git rev-parse {}^{{tree}}", target)) {
+            Ok(target_tree) => !merged_tree.is_empty() && merged_tree == target_tree.trim(),
+            Err(_) => false
+        }
+
+    }
+
     ///
     /// Returns the status of the current git repository
     /// 
@@ -168,11 +468,19 @@ impl GitV2 {
         }
     }
 
+    ///
+    /// Merges the source branch into the target branch,
+    /// locally, using the given strategy. The target branch
+    /// is left checked out. Gives an error when the merge
+    /// can't be done (a fast-forward is not possible or
+    /// there are conflicts), so nothing is silently lost.
+    /// 
     pub fn merge_local (
         source_branch_prefix: Option<&str>, 
         source_branch_name: &str,
         target_branch_prefix: Option<&str>,
-        target_branch_name: &str
+        target_branch_name: &str,
+        strategy: MergeStrategy
     ) -> Result<String, String> {
 
         let source_branch_prefix = match source_branch_prefix {
@@ -185,18 +493,86 @@ impl GitV2 {
             None => "",
         };
 
-        return Runner::run(
-            &format!(
-                "git fetch . {}{}:{}{}", 
-                source_branch_prefix, 
-                source_branch_name, 
-                target_branch_prefix, 
-                target_branch_name
-            )
-        );
+        let source = format!("{}{}", source_branch_prefix, source_branch_name);
+        let target = format!("{}{}", target_branch_prefix, target_branch_name);
+
+        let message = format!("Merge branch '{}' into {}", source, target);
+
+        match strategy {
+
+            MergeStrategy::FastForward => {
+                GitV2::checkout(None, &target, false)?;
+                Runner::run(&format!("git merge --ff-only {}", source))
+            },
+
+            MergeStrategy::NoFastForward => {
+                GitV2::checkout(None, &target, false)?;
+                Runner::run(&format!("git merge --no-ff -m {} {}", Runner::quote(&message), source))
+            },
+
+            MergeStrategy::Squash => {
+                let message = GitV2::squash_message(&source, &target)?;
+                GitV2::checkout(None, &target, false)?;
+                Runner::run(&format!("git merge --squash {}", source))?;
+                GitV2::commit(message, false)
+            },
+
+            MergeStrategy::Rebase => {
+                Runner::run(&format!("git rebase {} {}", target, source))?;
+                GitV2::checkout(None, &target, false)?;
+                Runner::run(&format!("git merge --ff-only {}", source))
+            },
+
+        }
 
     }
 
+    ///
+    /// The message of the commit squashing the source branch
+    /// into the target: the squashed commits are listed in
+    /// its body.
+    /// 
+    pub fn squash_message (source: &str, target: &str) -> Result<String, String> {
+
+        let squashed: Vec<String> = GitV2::commits_between(Some(target), source)?
+            .iter()
+            .filter(|commit| !commit.is_merge())
+            .map(|commit| format!("* {}", commit.subject))
+            .collect();
+
+        Ok(format!("Squash branch '{}' into {}\n\n{}", source, target, squashed.join("\n")))
+
+    }
+
+    ///
+    /// Concludes a merge stopped by conflicts (already resolved)
+    /// 
+    pub fn merge_continue () -> Result<String, String> {
+        Runner::run("git commit --no-edit")
+    }
+
+    ///
+    /// Concludes a rebase stopped by conflicts (already resolved)
+    /// 
+    pub fn rebase_continue () -> Result<String, String> {
+        Runner::run("git -c core.editor=true rebase --continue")
+    }
+
+    ///
+    /// Gives up the merge (or rebase) stopped by conflicts,
+    /// going back to the state before it started.
+    /// 
+    pub fn merge_abort () -> Result<String, String> {
+        if GitV2::is_rebasing() {
+            Runner::run("git rebase --abort")
+        } else if GitV2::is_merging() {
+            Runner::run("git merge --abort")
+        } else {
+            // A squash merge leaves no MERGE_HEAD behind
+            Runner::run("git reset --merge")
+        }
+    }
+
     ///
     /// Commits the changes with the given message
     /// 
@@ -207,8 +583,46 @@ impl GitV2 {
             false => "",
         };
 
-        return Runner::run(&format!("git commit -m \"{}\" {}", message, allow_empty));
+        return Runner::run(&format!("git commit -m {} {}", Runner::quote(&message), allow_empty));
+
+    }
+
+    ///
+    /// Creates an annotated tag, with the given message,
+    /// pointing to the given commit (or branch).
+    /// 
+    pub fn tag (tag_name: &str, message: &str, commit: &str) -> Result<String, String> {
+        Runner::run(&format!("git tag -a {} -m {} {}", tag_name, Runner::quote(message), commit))
+    }
+
+    ///
+    /// Lists the tags reachable from the given branch
+    /// 
+    pub fn tags (branch_fullname: &str) -> Result<Vec<String>, String> {
+        match Runner::run(&format!("git tag --merged {}", branch_fullname)) {
+            Ok(output) => Ok(
+                output
+                    .lines()
+                    .map(|line| line.trim().to_string())
+                    .filter(|line| !line.is_empty())
+                    .collect()
+            ),
+            Err(e) => Err(e)
+        }
+    }
 
+    ///
+    /// Detaches HEAD from the checked out branch, keeping the working tree
+    /// 
+    pub fn detach () -> Result<String, String> {
+        Runner::run("git checkout --detach")
+    }
+
+    ///
+    /// Removes the given tag
+    /// 
+    pub fn delete_tag (tag_name: &str) -> Result<String, String> {
+        Runner::run(&format!("git tag -d {}", tag_name))
     }
 
     ///
@@ -231,12 +645,29 @@ impl GitV2 {
 
     }
 
+    ///
+    /// Checks out the given branch, throwing away the changes of
+    /// the working tree and the index
+    ///
+    pub fn checkout_discarding (branch_fullname: &str) -> Result<String, String> {
+        Runner::run(&format!("git checkout --force {}", branch_fullname))
+    }
+
+    ///
+    /// Creates the local branch from the one of the same name on the
+    /// remote, tracking it, and checks it out
+    ///
+    pub fn checkout_tracking (branch_fullname: &str) -> Result<String, String> {
+        Runner::run(&format!("git checkout -b {} --track origin/{}", branch_fullname, branch_fullname))
+    }
+
     ///
     /// Removes the given local branch.
     /// 
     /// ### Parameters
     /// 
     /// * `branch_prefix` - The prefix of the branch to be removed
+    /// * `force` - Removes it even if git doesn't see it merged (`-D`)
     /// 
     /// ### Returns
     /// 
@@ -245,25 +676,124 @@ impl GitV2 {
     /// ### Example
     /// 
     /// ```rust
-    /// match GitV2::remove_local_branch(Some("feature/"), "my-feature") {
+    /// match GitV2::remove_local_branch(Some("feature/"), "my-feature", false) {
     ///     Ok(output) => success!("Branch removed: {}", output),
     ///     Err(error_message) => error!("Error removing branch: {}", error_message)
     /// }
     /// ```
     /// 
-    pub fn remove_local_branch (branch_prefix: Option<&str>, branch_name: &str) -> Result<String, String> {
+    pub fn remove_local_branch (branch_prefix: Option<&str>, branch_name: &str, force: bool) -> Result<String, String> {
 
         let branch_prefix = match branch_prefix {
             Some(prefix) => prefix,
             None => "",
         };
 
-        let command = format!("git branch -D {}{}", branch_prefix, branch_name);
+        let command = if force {
+            format!("git branch -D {}{}", branch_prefix, branch_name)
+        } else {
+            format!("git branch -d {}{}", branch_prefix, branch_name)
+        };
 
         Runner::run(&command)
 
     }
 
+    ///
+    /// Renames the local branch, along with its config
+    /// (`branch.<name>.*`) and its reflog
+    ///
+    pub fn rename_branch (from: &str, to: &str) -> Result<String, String> {
+        Runner::run(&format!("git branch -m {} {}", from, to))
+    }
+
+    ///
+    /// Makes the local branch track the one of the same name on the remote
+    ///
+    pub fn set_upstream (branch_fullname: &str) -> Result<String, String> {
+        Runner::run(&format!("git branch --set-upstream-to=origin/{} {}", branch_fullname, branch_fullname))
+    }
+
+    ///
+    /// Returns the names of the local branches
+    ///
+    pub fn local_branches () -> Result<Vec<String>, String> {
+        match Runner::run("git for-each-ref --format='%(refname:short)' refs/heads/") {
+            Ok(output) => Ok(
+                output
+                    .lines()
+                    .map(|line| line.trim().to_string())
+                    .filter(|line| !line.is_empty())
+                    .collect()
+            ),
+            Err(e) => Err(e)
+        }
+    }
+
+    ///
+    /// Returns the names of the branches of the origin remote,
+    /// as of the last fetch (without the `origin/` prefix)
+    /// 
+    pub fn remote_branches () -> Result<Vec<String>, String> {
+        match Runner::run("git for-each-ref --format='%(refname:short)' refs/remotes/origin/") {
+            Ok(output) => Ok(
+                output
+                    .lines()
+                    .filter_map(|line| line.trim().strip_prefix("origin/"))
+                    .filter(|name| !name.is_empty() && *name != "HEAD")
+                    .map(|name| name.to_string())
+                    .collect()
+            ),
+            Err(e) => Err(e)
+        }
+    }
+
+    ///
+    /// Returns who authored the latest commit of the reference, and when
+    /// 
+    pub fn last_commit (reference: &str) -> Result<Signature, String> {
+
+        let output = Runner::run(&format!("git log -1 --format=%at%x1f%aI%x1f%an%x1f%ae {}", reference))?;
+
+        let fields: Vec<&str> = output.trim().split('\x1f').collect();
+
+        match fields.as_slice() {
+            [timestamp, date, author, email] => Ok(Signature {
+                timestamp: timestamp.parse::<i64>().unwrap_or_default(),
+                date: date.to_string(),
+                author: author.to_string(),
+                email: email.to_string(),
+            }),
+            _ => Err(format!("Couldn't read the latest commit of {}", reference))
+        }
+
+    }
+
+    ///
+    /// Returns the email of the user, as git knows it
+    /// 
+    pub fn user_email () -> Option<String> {
+        match Runner::run("git config --get user.email") {
+            Ok(email) if !email.trim().is_empty() => Some(email.trim().to_string()),
+            _ => None
+        }
+    }
+
+    ///
+    /// Returns the commit the branch was created at, from its reflog
+    /// (None when there is no reflog)
+    /// 
+    pub fn start_commit (branch_fullname: &str) -> Option<String> {
+        match Runner::run(&format!("git reflog show --format=%H refs/heads/{}", branch_fullname)) {
+            Ok(output) => output
+                .lines()
+                .map(|line| line.trim())
+                .rfind(|line| !line.is_empty())
+                .map(|commit| commit.to_string()),
+            Err(_) => None
+        }
+    }
+
     ///
     /// Creates a new branch with the given prefix (optional) and name
     /// 
@@ -370,6 +900,57 @@ impl GitV2 {
         )
     }
     
+    ///
+    /// Returns the commits reachable from `to` but not from `from`
+    /// (or every commit reachable from `to`), newest first.
+    /// 
+    pub fn commits_between (from: Option<&str>, to: &str) -> Result<Vec<Commit>, String> {
+
+        let range = match from {
+            Some(from) => format!("{}..{}", from, to),
+            None => to.to_string(),
+        };
+
+        // Fields are separated by \x1f and commits by \x1e
+        let command = format!("git log --format=%H%x1f%P%x1f%s%x1f%b%x1e {}", range);
+
+        let log = match Runner::run(&command) {
+            Ok(output) => output,
+            Err(err) => {
+                error!("{}", err);
+                return Err(err);
+            }
+        };
+
+        Ok(
+            log
+                .split('\x1e')
+                .map(|entry| entry.trim_start_matches('\n'))
+                .filter(|entry| !entry.trim().is_empty())
+                .filter_map(|entry| {
+                    let mut fields = entry.splitn(4, '\x1f');
+                    Some(Commit {
+                        hash: fields.next()?.to_string(),
+                        parents: fields.next()?.split_whitespace().map(|parent| parent.to_string()).collect(),
+                        subject: fields.next()?.to_string(),
+                        body: fields.next().unwrap_or("").trim().to_string(),
+                    })
+                })
+                .collect()
+        )
+
+    }
+
+    ///
+    /// Returns the given commit
+    /// 
+    pub fn read_commit (commit: &str) -> Result<Commit, String> {
+        match GitV2::commits_between(None, &format!("-1 {}", commit))?.into_iter().next() {
+            Some(commit) => Ok(commit),
+            None => Err(format!("Unknown commit '{}'", commit))
+        }
+    }
+
     ///
     /// Returns a list of branches that contain the given commit.
     /// The given branch is excluded from the list.
@@ -0,0 +1,297 @@
+use crate::command::runner::Runner;
+
+///
+/// HTTP calls to the servers lab-flow talks to (Jira, GitLab),
+/// made with `curl`, and the little JSON they need.
+///
+pub enum Http {}
+
+impl Http {
+
+    ///
+    /// Calls the URL, giving the body of the answer. Answers other
+    /// than 2xx are errors. `credentials` are curl config lines
+    /// authenticating the call (see `credential`): they are given
+    /// to curl on its stdin, so they never show in the command
+    /// line (nor in the log).
+    ///
+    pub fn request (method: &str, url: &str, credentials: &str, body: Option<&str>) -> Result<String, String> {
+
+        let data = match body {
+            Some(body) => format!(" -H 'Content-Type: application/json' -d {}", Runner::quote(body)),
+            None => String::new()
+        };
+
+        // The status code comes last, in a line of its own
+        let command = format!(
+            "curl -sS -X {} -H 'Accept: application/json'{} -w '\\n%{{http_code}}' {}",
+            method,
+            data,
+            Runner::quote(url)
+        );
+
+        let output = match credentials.is_empty() {
+            true => Runner::run(&command)?,
+            false => Runner::run_with_input(&format!("{} -K -", command), credentials)?
+        };
+
+        let (answer, status) = match output.trim_end().rsplit_once('\n') {
+            Some((answer, status)) => (answer.to_string(), status.trim().to_string()),
+            None => (String::new(), output.trim().to_string())
+        };
+
+        match status.starts_with('2') {
+            true => Ok(answer),
+            false => Err(format!("{} {} answered {}: {}", method, url, status, answer.trim()))
+        }
+
+    }
+
+    ///
+    /// A curl config line giving the option (`header`, `user`) the
+    /// value, for `request`.
+    ///
+    pub fn credential (option: &str, value: &str) -> String {
+        format!("{} = \"{}\"\n", option, value.replace('\\', "\\\\").replace('"', "\\\""))
+    }
+
+    ///
+    /// Quotes the text as a JSON string.
+    ///
+    pub fn json_string (text: &str) -> String {
+
+        let mut json = String::from("\"");
+
+        for current in text.chars() {
+            match current {
+                '"' => json.push_str("\\\""),
+                '\\' => json.push_str("\\\\"),
+                '\n' => json.push_str("\\n"),
+                '\r' => json.push_str("\\r"),
+                '\t' => json.push_str("\\t"),
+                control if control.is_control() => json.push_str(&format!("\\u{:04x}", control as u32)),
+                other => json.push(other)
+            }
+        }
+
+        json.push('"');
+
+        json
+
+    }
+
+    ///
+    /// The text of the member with the given name in the JSON
+    /// object (not in the ones nested in it), unescaped.
+    ///
+    pub fn json_field (json: &str, name: &str) -> Option<String> {
+        Http::json_value(json, name).and_then(Http::json_text)
+    }
+
+    ///
+    /// The value (as JSON text) of the member with the given name
+    /// in the JSON object, not looking into the nested ones.
+    ///
+    pub fn json_value<'a> (json: &'a str, name: &str) -> Option<&'a str> {
+        items(json, '{', '}')?
+            .into_iter()
+            .find_map(|member| {
+                let (key, value) = split_member(member)?;
+                match unescape(key) == name {
+                    true => Some(value),
+                    false => None
+                }
+            })
+    }
+
+    ///
+    /// The elements (as JSON text) of the JSON array.
+    ///
+    pub fn json_elements (json: &str) -> Vec<&str> {
+        items(json, '[', ']').unwrap_or_default()
+    }
+
+    ///
+    /// The text of a JSON string (unescaped) or number. Objects,
+    /// arrays and null have none.
+    ///
+    pub fn json_text (value: &str) -> Option<String> {
+
+        let value = value.trim();
+
+        if let Some(quoted) = value.strip_prefix('"').and_then(|value| value.strip_suffix('"')) {
+            return Some(unescape(quoted));
+        }
+
+        match value.starts_with(['{', '[']) || value == "null" || value.is_empty() {
+            true => None,
+            false => Some(value.to_string())
+        }
+
+    }
+
+}
+
+///
+/// Unescapes the inside of a JSON string.
+///
+fn unescape (value: &str) -> String {
+
+    let mut unescaped = String::new();
+    let mut chars = value.chars();
+
+    while let Some(current) = chars.next() {
+
+        if current != '\\' {
+            unescaped.push(current);
+            continue;
+        }
+
+        match chars.next() {
+            Some('n') => unescaped.push('\n'),
+            Some('r') => unescaped.push('\r'),
+            Some('t') => unescaped.push('\t'),
+            Some('u') => {
+                let code: String = chars.by_ref().take(4).collect();
+                if let Some(decoded) = u32::from_str_radix(&code, 16).ok().and_then(char::from_u32) {
+                    unescaped.push(decoded);
+                }
+            },
+            Some(other) => unescaped.push(other),
+            None => {}
+        }
+
+    }
+
+    unescaped
+
+}
+
+///
+/// The items of the JSON object (its members) or array (its
+/// elements), as JSON text: commas inside strings or nested
+/// values don't split them.
+///
+fn items (json: &str, open: char, close: char) -> Option<Vec<&str>> {
+
+    let inside = json.trim().strip_prefix(open)?.strip_suffix(close)?;
+
+    let mut items: Vec<&str> = Vec::new();
+    let mut depth = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut start = 0;
+
+    for (index, current) in inside.char_indices() {
+
+        if in_string {
+            match (escaped, current) {
+                (true, _) => escaped = false,
+                (false, '\\') => escaped = true,
+                (false, '"') => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+
+        match current {
+            '"' => in_string = true,
+            '{' | '[' => depth += 1,
+            '}' | ']' => depth -= 1,
+            ',' if depth == 0 => {
+                items.push(inside[start..index].trim());
+                start = index + 1;
+            },
+            _ => {}
+        }
+
+    }
+
+    let last = inside[start..].trim();
+
+    if !last.is_empty() {
+        items.push(last);
+    }
+
+    Some(items)
+
+}
+
+///
+/// Splits an object member (`"name": value`) into the inside of
+/// its name and its value.
+///
+fn split_member (member: &str) -> Option<(&str, &str)> {
+
+    let quoted = member.strip_prefix('"')?;
+
+    let mut escaped = false;
+
+    for (index, current) in quoted.char_indices() {
+        match (escaped, current) {
+            (true, _) => escaped = false,
+            (false, '\\') => escaped = true,
+            (false, '"') => return Some((&quoted[..index], quoted[index + 1..].trim_start().strip_prefix(':')?.trim())),
+            _ => {}
+        }
+    }
+
+    None
+
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::Http;
+
+    #[test]
+    fn field_is_unescaped () {
+
+        let json = r#"{"title": "A \"quoted\" title\\ with\ta tab\nand a line", "n": "caf\u00e9 \u2713"}"#;
+
+        assert_eq!(Http::json_field(json, "title"), Some(String::from("A \"quoted\" title\\ with\ta tab\nand a line")));
+        assert_eq!(Http::json_field(json, "n"), Some(String::from("café ✓")));
+
+    }
+
+    #[test]
+    fn field_is_only_a_member_of_the_object () {
+
+        let json = r#"{"author": {"web_url": "https://gitlab.acme.com/me", "title": "nested"}, "text": "\"web_url\": \"fake\"", "web_url": "https://gitlab.acme.com/g/p/-/issues/1"}"#;
+
+        assert_eq!(Http::json_field(json, "web_url"), Some(String::from("https://gitlab.acme.com/g/p/-/issues/1")));
+        assert_eq!(Http::json_field(json, "title"), None);
+        assert_eq!(Http::json_field("[{\"title\": \"in an array\"}]", "title"), None);
+
+    }
+
+    #[test]
+    fn field_may_be_a_number_but_not_an_object () {
+
+        let json = r#"{"id": 10000, "fields": {"summary": "x"}, "empty": null}"#;
+
+        assert_eq!(Http::json_field(json, "id"), Some(String::from("10000")));
+        assert_eq!(Http::json_field(json, "fields"), None);
+        assert_eq!(Http::json_field(json, "empty"), None);
+        assert_eq!(Http::json_value(json, "fields"), Some(r#"{"summary": "x"}"#));
+
+    }
+
+    #[test]
+    fn elements_are_split_outside_strings_and_nested_values () {
+
+        let json = r#"[ {"a": "x, y", "b": [1, 2]}, "}, {", 3 ]"#;
+
+        assert_eq!(Http::json_elements(json), vec![r#"{"a": "x, y", "b": [1, 2]}"#, r#""}, {""#, "3"]);
+        assert_eq!(Http::json_elements("[]"), Vec::<&str>::new());
+        assert_eq!(Http::json_elements(r#"{"a": 1}"#), Vec::<&str>::new());
+
+    }
+
+    #[test]
+    fn strings_are_quoted () {
+        assert_eq!(Http::json_string("A \"b\"\\\n"), r#""A \"b\"\\\n""#);
+    }
+
+}
@@ -0,0 +1,338 @@
+use crate::command::http::Http;
+use crate::config::constants::{
+    JIRA_URL_KEY,
+    JIRA_USER_KEY,
+    JIRA_TOKEN_KEY,
+};
+use crate::config::store::Store;
+
+///
+/// A tiny client of the Jira REST API (v2), calling `curl`.
+/// The base URL may be any server speaking the same API,
+/// like a local stub.
+///
+#[derive(Debug, PartialEq, Clone)]
+pub struct Jira {
+    /// The base URL of the server
+    pub url: String,
+    /// With a user (Jira Cloud), the token is an API token, sent
+    /// with basic authentication; without it, a personal access token
+    pub user: Option<String>,
+    pub token: String,
+}
+
+fn setting (key: &str) -> Option<String> {
+    match Store::get(key) {
+        Ok(value) if !value.trim().is_empty() => Some(String::from(value.trim())),
+        _ => None
+    }
+}
+
+impl Jira {
+
+    ///
+    /// The Jira server configured, if its base URL and token are.
+    ///
+    pub fn load () -> Result<Jira, String> {
+        match (setting(JIRA_URL_KEY), setting(JIRA_TOKEN_KEY)) {
+            (Some(url), Some(token)) => Ok(Jira {
+                url: String::from(url.trim_end_matches('/')),
+                user: setting(JIRA_USER_KEY),
+                token,
+            }),
+            _ => Err(format!("Jira isn't configured: set {} and {}.", JIRA_URL_KEY, JIRA_TOKEN_KEY))
+        }
+    }
+
+    ///
+    /// Calls the API, giving the body of the answer.
+    ///
+    fn request (&self, method: &str, path: &str, body: Option<&str>) -> Result<String, String> {
+
+        let credentials = match &self.user {
+            Some(user) => Http::credential("user", &format!("{}:{}", user, self.token)),
+            None => Http::credential("header", &format!("Authorization: Bearer {}", self.token))
+        };
+
+        Http::request(method, &format!("{}{}", self.url, path), &credentials, body)
+
+    }
+
+    ///
+    /// The web page of the issue.
+    ///
+    pub fn browse_url (&self, issue_key: &str) -> String {
+        format!("{}/browse/{}", self.url, issue_key)
+    }
+
+    ///
+    /// The summary (title) of the issue.
+    ///
+    pub fn summary (&self, issue_key: &str) -> Result<String, String> {
+
+        let issue = self.request("GET", &format!("/rest/api/2/issue/{}?fields=summary", urlencoding::encode(issue_key)), None)?;
+
+        match Http::json_value(&issue, "fields").and_then(|fields| Http::json_value(fields, "summary")).and_then(Http::json_text) {
+            Some(summary) => Ok(summary),
+            None => Err(format!("Jira gave no summary for {}.", issue_key))
+        }
+
+    }
+
+    ///
+    /// Moves the issue to the given status, through the transition
+    /// named after it (or leading to it).
+    ///
+    pub fn transition (&self, issue_key: &str, status: &str) -> Result<(), String> {
+
+        let path = format!("/rest/api/2/issue/{}/transitions", urlencoding::encode(issue_key));
+
+        let transitions = self.request("GET", &path, None)?;
+
+        // Each transition: {"id": "21", "name": "Review", "to": {"name": "In Review", ...}, ...}
+        let transition_id = Http::json_value(&transitions, "transitions")
+            .map(Http::json_elements)
+            .unwrap_or_default()
+            .into_iter()
+            .find_map(|transition| {
+
+                let id = Http::json_value(transition, "id").and_then(Http::json_text)?;
+                let name = Http::json_value(transition, "name").and_then(Http::json_text);
+                let to_status = Http::json_value(transition, "to").and_then(|to| Http::json_value(to, "name")).and_then(Http::json_text);
+
+                match [name, to_status].iter().flatten().any(|candidate| candidate.eq_ignore_ascii_case(status)) {
+                    true => Some(id),
+                    false => None
+                }
+
+            });
+
+        let transition_id = match transition_id {
+            Some(transition_id) => transition_id,
+            None => return Err(format!("{} can't be moved to '{}' (it may be there already).", issue_key, status))
+        };
+
+        self.request("POST", &path, Some(&format!("{{\"transition\":{{\"id\":{}}}}}", Http::json_string(&transition_id))))?;
+
+        Ok(())
+
+    }
+
+    ///
+    /// Links the given URL (a merge request) to the issue.
+    ///
+    pub fn add_link (&self, issue_key: &str, url: &str, title: &str) -> Result<(), String> {
+
+        let path = format!("/rest/api/2/issue/{}/remotelink", urlencoding::encode(issue_key));
+
+        let body = format!(
+            "{{\"globalId\":{},\"object\":{{\"url\":{},\"title\":{}}}}}",
+            Http::json_string(url),
+            Http::json_string(url),
+            Http::json_string(title)
+        );
+
+        self.request("POST", &path, Some(&body))?;
+
+        Ok(())
+
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::sync::mpsc::{channel, Receiver};
+
+    use super::Jira;
+
+    ///
+    /// A request received by the stub: its request line, its
+    /// headers and its body.
+    ///
+    struct Received {
+        line: String,
+        headers: Vec<String>,
+        body: String,
+    }
+
+    ///
+    /// Serves the given answers (status and body), one per
+    /// connection, on a local port: gives the Jira pointing at it
+    /// and what it receives.
+    ///
+    fn stub (answers: Vec<(u16, &'static str)>) -> (Jira, Receiver<Received>) {
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let (sender, receiver) = channel();
+
+        std::thread::spawn(move || {
+
+            for (status, answer) in answers {
+
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+
+                let mut headers: Vec<String> = Vec::new();
+
+                loop {
+                    let mut header = String::new();
+                    reader.read_line(&mut header).unwrap();
+                    if header.trim().is_empty() {
+                        break;
+                    }
+                    headers.push(String::from(header.trim()));
+                }
+
+                let length = headers
+                    .iter()
+                    .find_map(|header| header.to_lowercase().strip_prefix("content-length:").map(|length| length.trim().parse::<usize>().unwrap()))
+                    .unwrap_or(0);
+
+                let mut body = vec![0; length];
+                reader.read_exact(&mut body).unwrap();
+
+                sender.send(Received { line: String::from(line.trim()), headers, body: String::from_utf8(body).unwrap() }).unwrap();
+
+                write!(
+                    stream,
+                    "HTTP/1.1 {} Stub\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    answer.len(),
+                    answer
+                ).unwrap();
+
+            }
+
+        });
+
+        (Jira { url, user: None, token: String::from("t0k\"en") }, receiver)
+
+    }
+
+    #[test]
+    fn summary_is_read_from_the_fields () {
+
+        let (jira, received) = stub(vec![(200, r#"{"key":"ABC-1","fields":{"summary":"Add the \"login\" form"},"summary":"not this one"}"#)]);
+
+        assert_eq!(jira.summary("ABC-1"), Ok(String::from("Add the \"login\" form")));
+
+        let request = received.recv().unwrap();
+
+        assert_eq!(request.line, "GET /rest/api/2/issue/ABC-1?fields=summary HTTP/1.1");
+        assert!(request.headers.contains(&String::from("Authorization: Bearer t0k\"en")));
+
+    }
+
+    #[test]
+    fn summary_fails_on_errors () {
+
+        let (jira, _received) = stub(vec![(404, r#"{"errorMessages":["Issue does not exist"]}"#)]);
+
+        let error = jira.summary("ABC-404").unwrap_err();
+
+        assert!(error.contains("404"), "{}", error);
+        assert!(error.contains("Issue does not exist"), "{}", error);
+
+    }
+
+    #[test]
+    fn transition_is_found_by_its_target_status () {
+
+        // The fields in any order, with others around
+        let transitions = r#"{"expand":"transitions","transitions":[
+            {"name":"Start Progress","hasScreen":false,"to":{"self":"x","name":"In Progress","id":"3"},"id":"11"},
+            {"to":{"id":"4","statusCategory":{"name":"Done"},"name":"In Review"},"isGlobal":true,"id":"21","name":"Send to review"}
+        ]}"#;
+
+        let (jira, received) = stub(vec![(200, transitions), (204, "")]);
+
+        assert_eq!(jira.transition("ABC-1", "in review"), Ok(()));
+
+        assert_eq!(received.recv().unwrap().line, "GET /rest/api/2/issue/ABC-1/transitions HTTP/1.1");
+
+        let post = received.recv().unwrap();
+
+        assert_eq!(post.line, "POST /rest/api/2/issue/ABC-1/transitions HTTP/1.1");
+        assert_eq!(post.body, r#"{"transition":{"id":"21"}}"#);
+
+    }
+
+    #[test]
+    fn transition_is_found_by_its_name () {
+
+        let (jira, received) = stub(vec![(200, r#"{"transitions":[{"id":"11","name":"Start Progress","to":{"name":"In Progress"}}]}"#), (204, "")]);
+
+        assert_eq!(jira.transition("ABC-1", "Start Progress"), Ok(()));
+
+        received.recv().unwrap();
+
+        assert_eq!(received.recv().unwrap().body, r#"{"transition":{"id":"11"}}"#);
+
+    }
+
+    #[test]
+    fn transition_fails_when_none_leads_to_the_status () {
+
+        let (jira, received) = stub(vec![(200, r#"{"transitions":[{"id":"11","name":"Start Progress","to":{"name":"In Progress"}}]}"#)]);
+
+        assert!(jira.transition("ABC-1", "In Review").is_err());
+
+        received.recv().unwrap();
+
+        // Nothing posted
+        assert!(received.try_recv().is_err());
+
+    }
+
+    #[test]
+    fn transition_fails_when_the_post_is_refused () {
+
+        let (jira, _received) = stub(vec![
+            (200, r#"{"transitions":[{"id":"21","name":"Review","to":{"name":"In Review"}}]}"#),
+            (400, r#"{"errorMessages":["A resolution is required"]}"#),
+        ]);
+
+        let error = jira.transition("ABC-1", "In Review").unwrap_err();
+
+        assert!(error.contains("400"), "{}", error);
+
+    }
+
+    #[test]
+    fn link_is_posted_to_the_issue () {
+
+        let (mut jira, received) = stub(vec![(201, r#"{"id":10000}"#)]);
+
+        jira.user = Some(String::from("me@acme.com"));
+
+        assert_eq!(jira.add_link("ABC-1", "https://gitlab.acme.com/g/p/-/merge_requests/7", "Merge request of feature/ABC-1 into develop"), Ok(()));
+
+        let request = received.recv().unwrap();
+
+        assert_eq!(request.line, "POST /rest/api/2/issue/ABC-1/remotelink HTTP/1.1");
+        assert!(request.headers.iter().any(|header| header.starts_with("Authorization: Basic ")));
+        assert_eq!(
+            request.body,
+            r#"{"globalId":"https://gitlab.acme.com/g/p/-/merge_requests/7","object":{"url":"https://gitlab.acme.com/g/p/-/merge_requests/7","title":"Merge request of feature/ABC-1 into develop"}}"#
+        );
+
+    }
+
+    #[test]
+    fn link_fails_on_errors () {
+
+        let (jira, _received) = stub(vec![(403, r#"{"errorMessages":["No permission"]}"#)]);
+
+        assert!(jira.add_link("ABC-1", "https://gitlab.acme.com/g/p/-/merge_requests/7", "MR").unwrap_err().contains("403"));
+
+    }
+
+}
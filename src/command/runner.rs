@@ -1,6 +1,8 @@
-use std::process::{Command, Output};
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
 use std::env;
 
+use crate::config::constants::ALLOW_PROTECTED_ENV;
 use crate::working;
 
 pub enum Runner {}
@@ -22,7 +24,7 @@ impl Runner {
             },
 
             _ => {
-                os_command = format!("xdg-open {}", url);
+                os_command = format!("xdg-open {}", Runner::quote(url));
             }
 
         }
@@ -31,14 +33,45 @@ impl Runner {
 
     }
 
+    ///
+    /// Quotes the given text so the shell takes it
+    /// as a single argument, whatever it contains.
+    /// 
+    pub fn quote (text: &str) -> String {
+        if cfg!(target_os = "windows") {
+            format!("\"{}\"", text.replace('"', "\\\""))
+        } else {
+            format!("'{}'", text.replace('\'', "'\\''"))
+        }
+    }
+
     pub fn run (command: &str) -> Result<String, String> {
 
         working!("{}", command);
 
+        Runner::execute(command, None)
+
+    }
+
+    ///
+    /// Runs the command like `run`, writing the given input to its
+    /// stdin. Secrets (tokens) go this way: the command line of a
+    /// process can be read by the other users of the machine.
+    /// 
+    pub fn run_with_input (command: &str, input: &str) -> Result<String, String> {
+
+        working!("{}", command);
+
+        Runner::execute(command, Some(input))
+
+    }
+
+    fn execute (command: &str, input: Option<&str>) -> Result<String, String> {
+
         let command_result: Result<Output, std::io::Error> = if cfg!(target_os = "windows") {
-            run_for_windows(command)
+            run_for_windows(command, input)
         } else {
-            run_for_nix(command)
+            run_for_nix(command, input)
         };
 
         match command_result {
@@ -47,6 +80,9 @@ impl Runner {
 
                 if output.status.success() {
                     Ok(String::from_utf8_lossy(&output.stdout).to_string())
+                } else if output.stderr.is_empty() {
+                    // Some git commands explain their failures on stdout
+                    Err(String::from_utf8_lossy(&output.stdout).to_string())
                 } else {
                     Err(String::from_utf8_lossy(&output.stderr).to_string())
                 }
@@ -59,34 +95,67 @@ impl Runner {
 
     }
 
+    ///
+    /// Runs the program with the given arguments and extra
+    /// environment variables. Its output goes straight to the
+    /// console. Gives its exit code.
+    /// 
+    pub fn run_with_env (program: &str, args: &[String], envs: &[(String, String)]) -> Result<i32, String> {
+
+        working!("{} {}", program, args.join(" "));
+
+        match Command::new(program).args(args).envs(envs.iter().cloned()).status() {
+            Ok(status) => Ok(status.code().unwrap_or(-1)),
+            Err(command_error) => Err(format!("{:?}", command_error))
+        }
+
+    }
+
 }
 
-fn run_for_windows (command: &str) -> Result<Output, std::io::Error> {
+///
+/// Spawns the command, feeding it the input (if any), and waits for its output.
+///
+fn output_of (mut command: Command, input: Option<&str>) -> Result<Output, std::io::Error> {
 
-    match Command::new("cmd")
-            .args(["/C", command])
-            .output() {
+    // lab-flow's own merges may reach the protected branches
+    command.env(ALLOW_PROTECTED_ENV, "1");
 
-        Ok(res) => Ok(res),
-        
-        Err(cmd_error) => Err(cmd_error)
+    let input = match input {
+        Some(input) => input,
+        None => return command.output()
+    };
 
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(input.as_bytes())?;
     }
 
+    child.wait_with_output()
+
 }
 
-fn run_for_nix (command: &str) -> Result<Output, std::io::Error> {
+fn run_for_windows (command: &str, input: Option<&str>) -> Result<Output, std::io::Error> {
 
-    match Command::new("sh")
-            .args(["-c", command])
-            .output() {
+    let mut windows_command = Command::new("cmd");
 
-        Ok(res) => {
-            Ok(res)
-        },
+    windows_command.args(["/C", command]);
 
-        Err(sh_error) => Err(sh_error)
+    output_of(windows_command, input)
 
-    }
+}
+
+fn run_for_nix (command: &str, input: Option<&str>) -> Result<Output, std::io::Error> {
+
+    let mut nix_command = Command::new("sh");
+
+    nix_command.args(["-c", command]);
+
+    output_of(nix_command, input)
         
-}
\ No newline at end of file
+}
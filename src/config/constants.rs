@@ -6,5 +6,73 @@ pub const HOTFIX_BRANCH_NAME_KEY: &str = "lab.flow.branch.hotfix";
 pub const RELEASE_BRANCH_NAME_KEY: &str = "lab.flow.branch.release";
 pub const DEVELOP_BRANCH_NAME_KEY: &str = "lab.flow.branch.develop";
 pub const MAIN_BRANCH_NAME_KEY: &str = "lab.flow.branch.main";
+pub const SUPPORT_BRANCH_NAME_KEY: &str = "lab.flow.branch.support";
 
-pub const COMMIT_HASH_REGEX_PATTERN: &str = r"[A-Za-z0-9]{40}";
\ No newline at end of file
+pub const DEFAULT_SUPPORT_BRANCH_PREFIX: &str = "support/";
+
+// lab.flow.merge.default, or per type: lab.flow.merge.feature, lab.flow.merge.release...
+pub const MERGE_STRATEGY_KEY_PREFIX: &str = "lab.flow.merge";
+
+// Under .git/labflow/, while a finish waits for conflicts to be resolved
+pub const FINISH_STATE_FILE: &str = "finish";
+
+// Stored as branch.<BRANCH FULL NAME>.labflowbase
+pub const BRANCH_BASE_KEY_SUFFIX: &str = "labflowbase";
+
+// Custom branch types live under lab.flow.type.<TYPE>.<FIELD>
+pub const BRANCH_TYPE_KEY_PREFIX: &str = "lab.flow.type";
+
+// `git lab <ACTION>`, with no branch type before it: no custom type may be named like them
+pub const TOP_LEVEL_ACTIONS: [&str; 8] = ["init", "finish", "sync", "hooks", "lint", "status", "cleanup", "checkout"];
+
+pub const TAG_PREFIX_KEY: &str = "lab.flow.tag.prefix";
+pub const DEFAULT_PRE_RELEASE_ID: &str = "rc";
+
+// Multi-valued: PATH[:FORMAT[:PATTERN]]
+pub const VERSION_FILE_KEY: &str = "lab.flow.version.file";
+
+pub const SEMVER_REGEX_PATTERN: &str = r"^(?P<major>0|[1-9][0-9]*)\.(?P<minor>0|[1-9][0-9]*)\.(?P<patch>0|[1-9][0-9]*)(?:-(?P<pre>[0-9A-Za-z-]+(?:\.[0-9A-Za-z-]+)*))?(?:\+[0-9A-Za-z-]+(?:\.[0-9A-Za-z-]+)*)?$";
+
+pub const CHANGELOG_FILE_KEY: &str = "lab.flow.changelog.file";
+// "type" (Conventional Commits) or "branch" (merged feature/bugfix branches)
+pub const CHANGELOG_GROUP_KEY: &str = "lab.flow.changelog.group";
+pub const DEFAULT_CHANGELOG_FILE: &str = "CHANGELOG.md";
+
+pub const CONVENTIONAL_COMMIT_REGEX_PATTERN: &str = r"^(?P<type>[A-Za-z]+)(?:\((?P<scope>[^()]+)\))?(?P<breaking>!)?: (?P<description>.+)$";
+pub const MERGE_COMMIT_REGEX_PATTERN: &str = r"^Merge (?:remote-tracking )?branch '(?P<branch>[^']+)'";
+
+pub const COMMIT_HASH_REGEX_PATTERN: &str = r"[A-Za-z0-9]{40}";
+// Directory of the flow hooks (pre-flow-feature-start, ...), .git/hooks by default
+pub const HOOKS_PATH_KEY: &str = "lab.flow.hooks.path";
+
+// Lets commits and pushes reach the protected branches (main, develop)
+pub const ALLOW_PROTECTED_ENV: &str = "LABFLOW_ALLOW_PROTECTED";
+
+// Branch naming policy, per type: lab.flow.policy.<TYPE>.<RULE>
+pub const POLICY_KEY_PREFIX: &str = "lab.flow.policy";
+// The pattern of the issue tracker keys (JIRA-1)
+pub const ISSUE_PATTERN_KEY: &str = "lab.flow.policy.issue";
+pub const DEFAULT_ISSUE_PATTERN: &str = r"[A-Z][A-Z0-9]+-[0-9]+";
+
+// Conventional Commits rules: lab.flow.lint.<RULE>
+pub const LINT_KEY_PREFIX: &str = "lab.flow.lint";
+pub const DEFAULT_COMMIT_TYPES: &str = "feat,fix,docs,style,refactor,perf,test,build,ci,chore,revert";
+pub const DEFAULT_COMMIT_SUBJECT_MAX_LENGTH: usize = 72;
+
+// Jira integration: lab.flow.jira.url, .user (Jira Cloud), .token, .start and .finish
+pub const JIRA_URL_KEY: &str = "lab.flow.jira.url";
+pub const JIRA_USER_KEY: &str = "lab.flow.jira.user";
+pub const JIRA_TOKEN_KEY: &str = "lab.flow.jira.token";
+// The status the issue moves to on start and on finish
+pub const JIRA_START_STATUS_KEY: &str = "lab.flow.jira.start";
+pub const JIRA_FINISH_STATUS_KEY: &str = "lab.flow.jira.finish";
+pub const DEFAULT_JIRA_START_STATUS: &str = "In Progress";
+pub const DEFAULT_JIRA_FINISH_STATUS: &str = "In Review";
+
+// GitLab API: lab.flow.gitlab.url (taken from the remote by default) and .token
+pub const GITLAB_URL_KEY: &str = "lab.flow.gitlab.url";
+pub const GITLAB_TOKEN_KEY: &str = "lab.flow.gitlab.token";
+
+// The merge request seeded from an issue: branch.<BRANCH FULL NAME>.labflowtitle and .labflowdescription
+pub const BRANCH_TITLE_KEY_SUFFIX: &str = "labflowtitle";
+pub const BRANCH_DESCRIPTION_KEY_SUFFIX: &str = "labflowdescription";
\ No newline at end of file
@@ -1 +1,10 @@
-pub const INIT_ERROR_CODE: i32 = -1;
\ No newline at end of file
+pub const INIT_ERROR_CODE: i32 = -1;
+pub const ACTION_ERROR_CODE: i32 = 1;
+
+// Pre-flight checks of start and finish
+pub const NOT_INITIATED_ERROR_CODE: i32 = 2;
+pub const DIRTY_TREE_ERROR_CODE: i32 = 3;
+pub const DETACHED_HEAD_ERROR_CODE: i32 = 4;
+pub const OPERATION_IN_PROGRESS_ERROR_CODE: i32 = 5;
+pub const BASE_BEHIND_ERROR_CODE: i32 = 6;
+pub const LINT_ERROR_CODE: i32 = 7;
\ No newline at end of file
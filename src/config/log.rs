@@ -1,7 +1,26 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Set when the output is meant for scripts (`--format json`) or git hooks
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+///
+/// Silences the progress messages (info, success and working):
+/// only errors and warnings are shown, on stderr.
+/// 
+pub fn set_quiet (quiet: bool) {
+    QUIET.store(quiet, Ordering::Relaxed)
+}
+
+pub fn is_quiet () -> bool {
+    QUIET.load(Ordering::Relaxed)
+}
+
 #[macro_export]
 macro_rules! info {
     ($($arg:tt)*) => {
-        println!("💬 {}", format_args!($($arg)*))
+        if !$crate::config::log::is_quiet() {
+            println!("💬 {}", format_args!($($arg)*))
+        }
     };
 }
 
@@ -12,16 +31,27 @@ macro_rules! error {
     };
 }
 
+#[macro_export]
+macro_rules! warning {
+    ($($arg:tt)*) => {
+        eprintln!("⚠️ {}", format_args!($($arg)*))
+    };
+}
+
 #[macro_export]
 macro_rules! success {
     ($($arg:tt)*) => {
-        println!("✅ {}", format_args!($($arg)*))
+        if !$crate::config::log::is_quiet() {
+            println!("✅ {}", format_args!($($arg)*))
+        }
     };
 }
 
 #[macro_export]
 macro_rules! working {
     ($($arg:tt)*) => {
-        println!("👷 {}", format_args!($($arg)*))
+        if !$crate::config::log::is_quiet() {
+            println!("👷 {}", format_args!($($arg)*))
+        }
     };
 }
\ No newline at end of file
@@ -42,4 +42,54 @@ impl Store {
         Runner::run(&format!("git config --local --add {} {}", branch_name, value))
     }
 
+    ///
+    /// Stores the given value for the given key, replacing
+    /// the value already stored (if any).
+    /// 
+    pub fn set (key: &str, value: &str) -> Result<String, String> {
+        Runner::run(&format!("git config --local {} {}", key, Runner::quote(value)))
+    }
+
+    ///
+    /// Retrieves every value stored for the given key
+    /// (multi-valued keys are created with `--add`).
+    /// Gives an error if the key does not exist.
+    /// 
+    pub fn get_all (key: &str) -> Result<Vec<String>, String> {
+        match Runner::run(&format!("git config --local --get-all {}", key)) {
+            Ok(output) => Ok(
+                output
+                    .lines()
+                    .map(|line| line.trim().to_string())
+                    .filter(|line| !line.is_empty())
+                    .collect()
+            ),
+            Err(e) => Err(e)
+        }
+    }
+
+    ///
+    /// Retrieves every key (and its value) matching the
+    /// given regular expression. No match gives an empty
+    /// list instead of an error.
+    /// 
+    pub fn get_regexp (pattern: &str) -> Vec<(String, String)> {
+        match Runner::run(&format!("git config --local --get-regexp '{}'", pattern)) {
+            Ok(output) => {
+                output
+                    .lines()
+                    .filter_map(|line| {
+                        let mut parts = line.trim().splitn(2, ' ');
+                        match (parts.next(), parts.next()) {
+                            (Some(key), Some(value)) => Some((key.to_string(), value.trim().to_string())),
+                            (Some(key), None) => Some((key.to_string(), String::new())),
+                            _ => None
+                        }
+                    })
+                    .collect()
+            },
+            Err(_) => Vec::new()
+        }
+    }
+
 }
\ No newline at end of file
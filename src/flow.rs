@@ -1,2 +1,21 @@
 pub mod branch;
-pub mod init;
\ No newline at end of file
+pub mod changelog;
+pub mod cleanup;
+pub mod custom;
+pub mod finish;
+pub mod githooks;
+pub mod hook;
+pub mod init;
+pub mod lint;
+pub mod list;
+pub mod manifest;
+pub mod policy;
+pub mod preflight;
+pub mod rename;
+pub mod status;
+pub mod strategy;
+pub mod switch;
+pub mod sync;
+pub mod tracker;
+pub mod transaction;
+pub mod version;
\ No newline at end of file
@@ -7,9 +7,16 @@ use crate::config::constants::{
     RELEASE_BRANCH_NAME_KEY,
     DEVELOP_BRANCH_NAME_KEY,
     MAIN_BRANCH_NAME_KEY,
+    SUPPORT_BRANCH_NAME_KEY,
+    DEFAULT_SUPPORT_BRANCH_PREFIX,
+    BRANCH_BASE_KEY_SUFFIX,
+    BRANCH_TITLE_KEY_SUFFIX,
+    BRANCH_DESCRIPTION_KEY_SUFFIX,
 };
 
 use crate::config::store::{Store};
+use crate::flow::custom::CustomBranch;
+use crate::flow::strategy::MergeStrategy;
 
 use crate::command::gitv2::GitV2;
 use crate::{info, error, working};
@@ -46,6 +53,12 @@ fn get_config_branch (prefix: Option<String>, name: &str) -> Result<Branch, Stri
         Err(_) => return Err("Bugfix branch prefix not found".to_string())
     };
 
+    // Repositories initiated before support branches existed don't have it stored
+    let support_prefix: String = match Store::get(SUPPORT_BRANCH_NAME_KEY) {
+        Ok(support_prefix) => String::from(support_prefix.trim()),
+        Err(_) => String::from(DEFAULT_SUPPORT_BRANCH_PREFIX)
+    };
+
     match prefix {
         Some(prefix) => {
 
@@ -59,6 +72,10 @@ fn get_config_branch (prefix: Option<String>, name: &str) -> Result<Branch, Stri
                 return Ok(Branch::Bugfix(name.to_string()));
             } else if prefix == release_prefix {
                 return Ok(Branch::Release(name.to_string()));
+            } else if prefix == support_prefix {
+                return Ok(Branch::Support(name.to_string()));
+            } else if let Some(custom) = CustomBranch::from_prefix(&prefix) {
+                return Ok(Branch::Custom(custom.name, name.to_string()));
             } else {
                 return Err(format!("Unknown branch prefix '{}'", prefix));
             }
@@ -87,6 +104,10 @@ pub enum Branch {
     Release(String),
     Develop(String),
     Main(String),
+    /// A long-lived maintenance branch, for older versions
+    Support(String),
+    /// A user-defined branch type: (type name, branch name)
+    Custom(String, String),
 }
 
 impl Branch {
@@ -117,7 +138,8 @@ impl Branch {
         let prefix: Option<String> = match branch_full_name.split("/").next() {
             Some(prefix) => {
 
-                if prefix.starts_with(name) {
+                // No slash: develop, main... (`feature/f` does have a prefix)
+                if !branch_full_name.contains('/') {
                     None
                 } else {
                     Some(format!("{}/", prefix))
@@ -142,6 +164,176 @@ impl Branch {
             Branch::Release(name) => name,
             Branch::Develop(name) => name,
             Branch::Main(name) => name,
+            Branch::Support(name) => name,
+            Branch::Custom(_, name) => name,
+        }
+    }
+
+    ///
+    /// The branch name with its prefix, as git knows it.
+    /// 
+    pub fn full_name (&self) -> String {
+        match self.prefix() {
+            Some(prefix) => format!("{}{}", prefix, self.name()),
+            None => self.name().to_string()
+        }
+    }
+
+    ///
+    /// Remembers, in the branch config, the base this branch
+    /// was started from (when it isn't the default one).
+    /// 
+    pub fn record_base (&self, base: &str) -> Result<String, String> {
+        Store::set(&format!("branch.{}.{}", self.full_name(), BRANCH_BASE_KEY_SUFFIX), base)
+    }
+
+    ///
+    /// The base recorded when this branch was started, if any.
+    /// 
+    pub fn recorded_base (&self) -> Option<String> {
+        match Store::get(&format!("branch.{}.{}", self.full_name(), BRANCH_BASE_KEY_SUFFIX)) {
+            Ok(base) => Some(String::from(base.trim())),
+            Err(_) => None
+        }
+    }
+
+    ///
+    /// Remembers, in the branch config, the title and the
+    /// description of its merge requests (taken from its issue).
+    /// 
+    pub fn record_merge_request (&self, title: &str, description: &str) -> Result<String, String> {
+        Store::set(&format!("branch.{}.{}", self.full_name(), BRANCH_TITLE_KEY_SUFFIX), title)?;
+        Store::set(&format!("branch.{}.{}", self.full_name(), BRANCH_DESCRIPTION_KEY_SUFFIX), description)
+    }
+
+    ///
+    /// The title and the description recorded for the merge
+    /// requests of this branch, if any.
+    /// 
+    pub fn recorded_merge_request (&self) -> (Option<String>, Option<String>) {
+
+        let recorded = |suffix: &str| match Store::get(&format!("branch.{}.{}", self.full_name(), suffix)) {
+            Ok(value) if !value.trim().is_empty() => Some(String::from(value.trim())),
+            _ => None
+        };
+
+        (recorded(BRANCH_TITLE_KEY_SUFFIX), recorded(BRANCH_DESCRIPTION_KEY_SUFFIX))
+
+    }
+
+    ///
+    /// Tells if finishing this branch into the given target
+    /// creates a tag: releases and hotfixes are tagged when
+    /// they reach the main branch or a support branch.
+    /// 
+    pub fn is_tagged_on (&self, target: &Branch) -> bool {
+        matches!(
+            (self, target),
+            (Branch::Release(_) | Branch::Hotfix(_), Branch::Main(_) | Branch::Support(_))
+        )
+    }
+
+    ///
+    /// The full name of the branch this branch is created
+    /// from: the one recorded at start, if any, or the
+    /// default for its type.
+    /// 
+    pub fn base (&self) -> Result<String, String> {
+
+        if let Some(base) = self.recorded_base() {
+            return Ok(base);
+        }
+
+        match self {
+            Branch::Custom(type_name, _) => CustomBranch::load(type_name).map(|custom| custom.base),
+            Branch::Support(_) => match Store::get(MAIN_BRANCH_NAME_KEY) {
+                Ok(main_name) => Ok(String::from(main_name.trim())),
+                Err(_) => Err("Main branch name not found".to_string())
+            },
+            _ => match Store::get(DEVELOP_BRANCH_NAME_KEY) {
+                Ok(develop_name) => Ok(String::from(develop_name.trim())),
+                Err(_) => Err("Develop branch name not found".to_string())
+            }
+        }
+    }
+
+    ///
+    /// Tells if the branch, at the given reference (itself, or
+    /// `origin/...`), has work of its own: it moved since it was
+    /// started, by its reflog, or its tip left the first-parent
+    /// line of its base. The reflog expires, and a branch checked
+    /// out from the remote has no commits of its own in it, so
+    /// the history alone is enough: a branch just started (or
+    /// fast-forwarded into its base) has no work.
+    ///
+    pub fn has_work (&self, reference: &str) -> bool {
+
+        let tip = match GitV2::rev_parse(reference) {
+            Ok(tip) => tip,
+            Err(_) => return false
+        };
+
+        if GitV2::start_commit(&self.full_name()).is_some_and(|start| start != tip) {
+            return true;
+        }
+
+        let base = match self.base() {
+            Ok(base) => base,
+            Err(_) => return false
+        };
+
+        let base_reference = match GitV2::rev_parse(&base) {
+            Ok(_) => base,
+            Err(_) => format!("origin/{}", base)
+        };
+
+        GitV2::rev_parse(&base_reference).is_ok() && !GitV2::is_on_first_parent(&tip, &base_reference)
+
+    }
+
+    ///
+    /// Tells if finishing this branch opens a merge request
+    /// (when there is a remote) or merges it locally.
+    /// 
+    pub fn opens_merge_request (&self) -> bool {
+        match self {
+            Branch::Custom(type_name, _) => match CustomBranch::load(type_name) {
+                Ok(custom) => custom.merge_request,
+                Err(_) => true
+            },
+            _ => true
+        }
+    }
+
+    ///
+    /// The strategy used to merge this branch locally.
+    /// 
+    pub fn merge_strategy (&self) -> MergeStrategy {
+        match self {
+            Branch::Custom(type_name, _) => match CustomBranch::load(type_name) {
+                Ok(custom) => custom.strategy,
+                Err(_) => MergeStrategy::default_strategy()
+            },
+            _ => match MergeStrategy::configured(&self.type_name()) {
+                Some(strategy) => strategy,
+                None => MergeStrategy::default_strategy()
+            }
+        }
+    }
+
+    ///
+    /// The branch type, as written in the command line.
+    /// 
+    pub fn type_name (&self) -> String {
+        match self {
+            Branch::Feature(_) => String::from("feature"),
+            Branch::Hotfix(_) => String::from("hotfix"),
+            Branch::Bugfix(_) => String::from("bugfix"),
+            Branch::Release(_) => String::from("release"),
+            Branch::Develop(_) => String::from("develop"),
+            Branch::Main(_) => String::from("main"),
+            Branch::Support(_) => String::from("support"),
+            Branch::Custom(type_name, _) => type_name.clone(),
         }
     }
 
@@ -171,11 +363,40 @@ impl Branch {
                     Err(_) => None,
                 }
             },
+            Branch::Support(_) => {
+                match Store::get(SUPPORT_BRANCH_NAME_KEY) {
+                    Ok(prefix) => Some(String::from(prefix.trim())),
+                    Err(_) => Some(String::from(DEFAULT_SUPPORT_BRANCH_PREFIX)),
+                }
+            },
+            Branch::Custom(type_name, _) => {
+                match CustomBranch::load(type_name) {
+                    Ok(custom) => Some(custom.prefix),
+                    Err(_) => None,
+                }
+            },
             _ => None,
         }
     }
 
     pub fn source (&self) -> Result<Vec<Branch>, String> {
+
+        // Custom branch types declare where they finish
+        if let Branch::Custom(type_name, _) = self {
+            return CustomBranch::load(type_name)?
+                .targets
+                .iter()
+                .map(|target| match Branch::from(target) {
+                    Some(branch) => Ok(branch),
+                    None => Err(format!("Unknown target branch '{}' for branch type '{}'", target, type_name))
+                })
+                .collect();
+        }
+
+        // Started from a support branch? It goes back only to it
+        if let Some(Branch::Support(support_name)) = self.recorded_base().and_then(|base| Branch::from(&base)) {
+            return Ok(vec![Branch::Support(support_name)]);
+        }
         
         let branch_name: String = String::from(self.name());
 
@@ -228,7 +449,7 @@ impl Branch {
             None => "",
         };    
 
-        let source_branches = match GitV2::source_branches(
+        let source_branches: Vec<Branch> = match GitV2::source_branches(
             &first_commit_not_in_branch, 
             branch_prefix, 
             &branch_name
@@ -261,6 +482,41 @@ impl Branch {
             },
         };
 
+        // 5. Features and bugfixes never go straight to main...
+        let source_branches: Vec<Branch> = match self {
+            Branch::Feature(_) | Branch::Bugfix(_) => {
+                let without_main: Vec<Branch> = source_branches
+                    .into_iter()
+                    .filter(|branch| !matches!(branch, Branch::Main(_)))
+                    .collect();
+                if without_main.is_empty() {
+                    let base = self.base()?;
+                    match Branch::from(&base) {
+                        Some(base_branch) => vec![base_branch],
+                        None => return Err(format!("Unknown base branch '{}'", base))
+                    }
+                } else {
+                    without_main
+                }
+            },
+            _ => source_branches
+        };
+
+        // 6. ... while releases and hotfixes always do (first, so it is tagged)
+        let source_branches = match self {
+            Branch::Release(_) | Branch::Hotfix(_) if !source_branches.iter().any(|branch| matches!(branch, Branch::Main(_))) => {
+                match Store::get(MAIN_BRANCH_NAME_KEY) {
+                    Ok(main_name) => {
+                        let mut with_main = vec![Branch::Main(String::from(main_name.trim()))];
+                        with_main.extend(source_branches);
+                        with_main
+                    },
+                    Err(_) => return Err("Main branch name not found".to_string())
+                }
+            },
+            _ => source_branches
+        };
+
         Ok(source_branches)
 
     }
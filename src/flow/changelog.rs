@@ -0,0 +1,242 @@
+use std::path::PathBuf;
+
+use regex::Regex;
+
+use crate::config::constants::{
+    CHANGELOG_FILE_KEY,
+    CHANGELOG_GROUP_KEY,
+    DEFAULT_CHANGELOG_FILE,
+    CONVENTIONAL_COMMIT_REGEX_PATTERN,
+    MERGE_COMMIT_REGEX_PATTERN,
+};
+
+use crate::config::store::Store;
+use crate::command::gitv2::{GitV2, Commit};
+use crate::command::runner::Runner;
+use crate::flow::branch::Branch;
+use crate::flow::version::Version;
+use crate::success;
+
+///
+/// How the changelog entries are grouped.
+///
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ChangelogGroup {
+    /// By Conventional Commit type (feat, fix, ...)
+    Type,
+    /// By the feature or bugfix branch that brought the commits
+    Branch,
+}
+
+///
+/// The changelog section of a release: the commits between
+/// the previous version tag and the release branch.
+///
+#[derive(Debug, PartialEq, Clone)]
+pub struct Changelog {
+    pub version: String,
+    pub section: String,
+}
+
+/// The headings, in order, of the commit types
+const TYPE_HEADINGS: [(&str, &str); 6] = [
+    ("feat", "Features"),
+    ("fix", "Bug Fixes"),
+    ("perf", "Performance"),
+    ("refactor", "Refactoring"),
+    ("docs", "Documentation"),
+    ("revert", "Reverts"),
+];
+
+const BREAKING_HEADING: &str = "Breaking Changes";
+const OTHER_HEADING: &str = "Other Changes";
+
+impl Changelog {
+
+    ///
+    /// Builds the changelog section for the given release.
+    ///
+    pub fn generate (release: &Branch) -> Result<Changelog, String> {
+
+        let release_fullname = release.full_name();
+
+        let version = match Version::parse(release.name()) {
+            Some(version) => version.to_string(),
+            None => release.name().to_string()
+        };
+
+        let previous_tag = Version::latest_tag(&release_fullname)?;
+
+        let commits: Vec<Commit> = GitV2::commits_between(previous_tag.as_deref(), &release_fullname)?
+            .into_iter()
+            // lab-flow's own bookkeeping commits
+            .filter(|commit| !commit.subject.starts_with("Bump version to ") && !commit.subject.starts_with("Update changelog for "))
+            .collect();
+
+        let groups = match Changelog::grouping() {
+            ChangelogGroup::Type => group_by_type(&commits)?,
+            ChangelogGroup::Branch => group_by_branch(&commits)?,
+        };
+
+        let mut section = format!("## {} ({})\n", version, today()?);
+
+        for (heading, entries) in groups.iter().filter(|(_, entries)| !entries.is_empty()) {
+            section.push_str(&format!("\n### {}\n\n", heading));
+            entries.iter().for_each(|entry| section.push_str(&format!("- {}\n", entry)));
+        }
+
+        Ok(Changelog { version, section })
+
+    }
+
+    fn grouping () -> ChangelogGroup {
+        match Store::get(CHANGELOG_GROUP_KEY) {
+            Ok(group) if group.trim() == "branch" => ChangelogGroup::Branch,
+            _ => ChangelogGroup::Type
+        }
+    }
+
+    ///
+    /// Prepends the section to the changelog file (right after
+    /// its title, if it has one) and commits it on the current
+    /// branch.
+    ///
+    pub fn prepend (&self) -> Result<(), String> {
+
+        let file_name = match Store::get(CHANGELOG_FILE_KEY) {
+            Ok(file_name) => String::from(file_name.trim()),
+            Err(_) => String::from(DEFAULT_CHANGELOG_FILE)
+        };
+
+        let path = PathBuf::from(GitV2::root_dir()?).join(&file_name);
+
+        let content = std::fs::read_to_string(&path).unwrap_or_default();
+
+        let updated = match content.split_once('\n') {
+            Some((title, rest)) if title.starts_with("# ") => {
+                format!("{}\n\n{}\n{}", title, self.section, rest.trim_start_matches('\n'))
+            },
+            _ if content.is_empty() => format!("# Changelog\n\n{}", self.section),
+            _ => format!("{}\n{}", self.section, content)
+        };
+
+        match std::fs::write(&path, updated) {
+            Ok(_) => {},
+            Err(e) => return Err(format!("Couldn't write {}: {}", file_name, e))
+        }
+
+        GitV2::add(vec![Runner::quote(&path.to_string_lossy())])?;
+        GitV2::commit(format!("Update changelog for {}", self.version), false)?;
+
+        success!("Changelog for {} written to {}", self.version, file_name);
+
+        Ok(())
+
+    }
+
+}
+
+fn conventional_entry (subject: &str, short_hash: &str, regex: &Regex) -> (String, bool, String) {
+    match regex.captures(subject) {
+        Some(captures) => {
+            let description = match captures.name("scope") {
+                Some(scope) => format!("**{}:** {} ({})", scope.as_str(), &captures["description"], short_hash),
+                None => format!("{} ({})", &captures["description"], short_hash),
+            };
+            (captures["type"].to_lowercase(), captures.name("breaking").is_some(), description)
+        },
+        None => (String::new(), false, format!("{} ({})", subject, short_hash))
+    }
+}
+
+fn group_by_type (commits: &[Commit]) -> Result<Vec<(String, Vec<String>)>, String> {
+
+    let regex = match Regex::new(CONVENTIONAL_COMMIT_REGEX_PATTERN) {
+        Ok(regex) => regex,
+        Err(e) => return Err(e.to_string())
+    };
+
+    let mut breaking: Vec<String> = Vec::new();
+    let mut typed: Vec<(String, Vec<String>)> = TYPE_HEADINGS
+        .iter()
+        .map(|(_, heading)| (heading.to_string(), Vec::new()))
+        .collect();
+    let mut other: Vec<String> = Vec::new();
+
+    for commit in commits.iter().filter(|commit| !commit.is_merge()) {
+
+        let (commit_type, is_breaking, entry) = conventional_entry(&commit.subject, commit.short_hash(), &regex);
+
+        if is_breaking || commit.body.contains("BREAKING CHANGE:") {
+            breaking.push(entry.clone());
+        }
+
+        match TYPE_HEADINGS.iter().position(|(type_name, _)| *type_name == commit_type) {
+            Some(index) => typed[index].1.push(entry),
+            None => other.push(entry)
+        }
+
+    }
+
+    let mut groups = vec![(BREAKING_HEADING.to_string(), breaking)];
+    groups.append(&mut typed);
+    groups.push((OTHER_HEADING.to_string(), other));
+
+    Ok(groups)
+
+}
+
+fn group_by_branch (commits: &[Commit]) -> Result<Vec<(String, Vec<String>)>, String> {
+
+    let regex = match Regex::new(MERGE_COMMIT_REGEX_PATTERN) {
+        Ok(regex) => regex,
+        Err(e) => return Err(e.to_string())
+    };
+
+    let mut groups: Vec<(String, Vec<String>)> = Vec::new();
+    let mut grouped_hashes: Vec<String> = Vec::new();
+
+    for merge in commits.iter().filter(|commit| commit.is_merge()) {
+
+        let branch_name = match regex.captures(&merge.subject) {
+            Some(captures) => captures["branch"].to_string(),
+            None => continue
+        };
+
+        // Only the feature and bugfix branches get their own group
+        match Branch::from(&branch_name) {
+            Some(Branch::Feature(_)) | Some(Branch::Bugfix(_)) => {},
+            _ => continue
+        }
+
+        let merged_commits = GitV2::commits_between(Some(&merge.parents[0]), &merge.parents[1])?;
+
+        let entries: Vec<String> = merged_commits
+            .iter()
+            .filter(|commit| !commit.is_merge())
+            .map(|commit| format!("{} ({})", commit.subject, commit.short_hash()))
+            .collect();
+
+        grouped_hashes.extend(merged_commits.iter().map(|commit| commit.hash.clone()));
+        groups.push((branch_name, entries));
+
+    }
+
+    let other: Vec<String> = commits
+        .iter()
+        .filter(|commit| !commit.is_merge() && !grouped_hashes.contains(&commit.hash))
+        .map(|commit| format!("{} ({})", commit.subject, commit.short_hash()))
+        .collect();
+
+    groups.push((OTHER_HEADING.to_string(), other));
+
+    Ok(groups)
+
+}
+
+///
+/// Today's date (in the local time zone) as YYYY-MM-DD.
+///
+fn today () -> Result<String, String> {
+    Ok(Runner::run("date +%F")?.trim().to_string())
+}
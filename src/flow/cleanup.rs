@@ -0,0 +1,207 @@
+use crate::command::gitv2::GitV2;
+use crate::flow::branch::Branch;
+use crate::flow::status::FlowBranch;
+use crate::{info, success, warning, working};
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+///
+/// Flags and options of `git lab cleanup`.
+///
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct CleanupOptions {
+    /// Removes without asking
+    pub yes: bool,
+    /// Removes the remote branches too
+    pub remote: bool,
+    /// Also removes the branches with no work of their own
+    /// and no commits for this many days
+    pub stale: Option<u32>,
+}
+
+///
+/// A branch to be removed, and why.
+///
+struct Removal {
+    flow_branch: FlowBranch,
+    reason: String,
+    local: bool,
+    remote: bool,
+}
+
+///
+/// `git lab cleanup`: removes, in bulk, the flow branches already
+/// merged (or abandoned), locally and on the remote.
+///
+pub enum Cleanup {}
+
+impl Cleanup {
+
+    ///
+    /// Why the branch (as seen locally or on the remote) can be
+    /// removed, if it can: it is merged into all of its targets
+    /// or, with `stale`, it has no commits of its own nor new
+    /// ones for that many days. Work not merged is never removed.
+    ///
+    fn reason (flow_branch: &FlowBranch, stale: Option<u32>, now: i64) -> Option<String> {
+
+        if flow_branch.is_merged() {
+            return Some(String::from("merged"));
+        }
+
+        let days = stale?;
+
+        match flow_branch.ahead_behind() {
+            Some((_, 0, _)) => {},
+            _ => return None
+        }
+
+        let last_commit = GitV2::last_commit(&flow_branch.reference()).ok()?;
+
+        match now - last_commit.timestamp >= days as i64 * SECONDS_PER_DAY {
+            true => Some(format!("no work of its own, nothing new for {} days", days)),
+            false => None
+        }
+
+    }
+
+    ///
+    /// What is removed: the local branches that can be and, with
+    /// `--remote`, the remote ones too.
+    ///
+    fn plan (options: &CleanupOptions) -> Result<Vec<Removal>, String> {
+
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs() as i64)
+            .unwrap_or_default();
+
+        let mut removals: Vec<Removal> = Vec::new();
+
+        // Support branches are long-lived
+        for flow_branch in FlowBranch::all()?.into_iter().filter(|flow_branch| !matches!(flow_branch.branch, Branch::Support(_))) {
+
+            let local_reason = match flow_branch.local {
+                true => Cleanup::reason(&flow_branch, options.stale, now),
+                false => None
+            };
+
+            let remote_view = FlowBranch { local: false, ..flow_branch.clone() };
+
+            // The remote branch goes with the local one when they are the same
+            let remote_reason = match (options.remote, flow_branch.remote) {
+                (true, true) => match &local_reason {
+                    Some(reason) if GitV2::rev_parse(&flow_branch.full_name) == GitV2::rev_parse(&remote_view.reference()) => Some(reason.clone()),
+                    _ => Cleanup::reason(&remote_view, options.stale, now)
+                },
+                _ => None
+            };
+
+            if local_reason.is_some() && flow_branch.remote && options.remote && remote_reason.is_none() {
+                warning!("{} has commits on the remote that weren't merged: only the local branch is removed", &flow_branch.full_name);
+            }
+
+            let reason = match local_reason.as_ref().or(remote_reason.as_ref()) {
+                Some(reason) => reason.clone(),
+                None => continue
+            };
+
+            removals.push(Removal {
+                local: local_reason.is_some(),
+                remote: remote_reason.is_some(),
+                flow_branch,
+                reason,
+            });
+
+        }
+
+        Ok(removals)
+
+    }
+
+    fn confirm () -> bool {
+
+        info!("Remove them? [y/N]");
+
+        let mut answer = String::new();
+
+        match std::io::stdin().read_line(&mut answer) {
+            Ok(_) => matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"),
+            Err(_) => false
+        }
+
+    }
+
+    pub fn run (options: &CleanupOptions) -> Result<(), String> {
+
+        if options.remote {
+
+            if !GitV2::is_remote() {
+                return Err(String::from("There is no remote server to clean up."));
+            }
+
+            working!("Fetching from the remote");
+
+            GitV2::fetch_prune()?;
+
+        }
+
+        let removals = Cleanup::plan(options)?;
+
+        if removals.is_empty() {
+            info!("No branch to clean up");
+            return Ok(());
+        }
+
+        for removal in removals.iter() {
+            let location = [(removal.local, "local"), (removal.remote, "remote")]
+                .iter()
+                .filter(|(removed, _)| *removed)
+                .map(|(_, location)| *location)
+                .collect::<Vec<&str>>()
+                .join(", ");
+            info!("  {} ({}): {}", removal.flow_branch.full_name, location, removal.reason);
+        }
+
+        if !options.yes && !Cleanup::confirm() {
+            info!("Nothing was removed");
+            return Ok(());
+        }
+
+        for removal in removals.iter() {
+            Cleanup::remove(removal)?;
+        }
+
+        Ok(())
+
+    }
+
+    fn remove (removal: &Removal) -> Result<(), String> {
+
+        let full_name = &removal.flow_branch.full_name;
+
+        if removal.local {
+
+            if GitV2::current_branch().as_deref() == Some(full_name.as_str()) {
+                GitV2::checkout(None, &removal.flow_branch.branch.base()?, false)?;
+            }
+
+            // Already checked: merged, or without work of its own
+            GitV2::remove_local_branch(None, full_name, true)?;
+
+            success!("Branch {} removed from local", full_name);
+
+        }
+
+        if removal.remote {
+            match GitV2::remove_remote_branch(full_name) {
+                Ok(_) => { success!("Branch {} removed from the remote", full_name); },
+                Err(e) => { warning!("Couldn't remove {} from the remote: {}", full_name, e.trim()); }
+            }
+        }
+
+        Ok(())
+
+    }
+
+}
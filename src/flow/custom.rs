@@ -0,0 +1,143 @@
+use crate::config::constants::{
+    BRANCH_TYPE_KEY_PREFIX,
+    DEVELOP_BRANCH_NAME_KEY,
+    TOP_LEVEL_ACTIONS,
+};
+
+use crate::config::store::Store;
+use crate::flow::strategy::MergeStrategy;
+
+///
+/// A branch type declared by the user, besides the built-in
+/// ones. It is read from the git config of the repository:
+///
+/// ```text
+/// git config --local lab.flow.type.chore.prefix chore/
+/// git config --local lab.flow.type.chore.base develop
+/// git config --local --add lab.flow.type.chore.target develop
+/// git config --local lab.flow.type.chore.strategy squash
+/// git config --local lab.flow.type.chore.mergerequest false
+/// ```
+///
+/// Only the prefix is mandatory. The base defaults to the
+/// develop branch, the targets default to the base, the
+/// strategy to `lab.flow.merge.<type>` or the default one
+/// (`lab.flow.merge.default`) and merge requests are opened.
+///
+/// A type can't be named like a top-level action (`status`,
+/// `sync`, `hooks`...): `git lab status` would never reach it.
+///
+#[derive(Debug, PartialEq, Clone)]
+pub struct CustomBranch {
+    pub name: String,
+    pub prefix: String,
+    pub base: String,
+    pub targets: Vec<String>,
+    pub strategy: MergeStrategy,
+    pub merge_request: bool,
+}
+
+fn key (type_name: &str, field: &str) -> String {
+    format!("{}.{}.{}", BRANCH_TYPE_KEY_PREFIX, type_name, field)
+}
+
+impl CustomBranch {
+
+    ///
+    /// Loads the custom branch type with the given name
+    /// from the store. Gives an error if the type is not
+    /// declared (it has no prefix) or is misconfigured.
+    ///
+    pub fn load (type_name: &str) -> Result<CustomBranch, String> {
+
+        let type_name = type_name.to_lowercase();
+
+        if TOP_LEVEL_ACTIONS.contains(&type_name.as_str()) {
+            return Err(format!("The branch type '{}' is named like the `git lab {}` command: give it another name.", type_name, type_name));
+        }
+
+        let prefix = match Store::get(&key(&type_name, "prefix")) {
+            Ok(prefix) => String::from(prefix.trim()),
+            Err(_) => return Err(format!("Unknown branch type '{}'", type_name))
+        };
+
+        let base = match Store::get(&key(&type_name, "base")) {
+            Ok(base) => String::from(base.trim()),
+            Err(_) => match Store::get(DEVELOP_BRANCH_NAME_KEY) {
+                Ok(develop_name) => String::from(develop_name.trim()),
+                Err(_) => return Err("Develop branch name not found".to_string())
+            }
+        };
+
+        let targets = match Store::get_all(&key(&type_name, "target")) {
+            Ok(targets) if !targets.is_empty() => targets,
+            _ => vec![base.clone()]
+        };
+
+        let strategy = match Store::get(&key(&type_name, "strategy")) {
+            Ok(text) => match MergeStrategy::parse(&text) {
+                Some(strategy) => strategy,
+                None => return Err(format!("Unknown merge strategy '{}' for branch type '{}'", text.trim(), type_name))
+            },
+            // As for the built-in types: `lab.flow.merge.<type>`, then the default one
+            Err(_) => match MergeStrategy::configured(&type_name) {
+                Some(strategy) => strategy,
+                None => MergeStrategy::default_strategy()
+            }
+        };
+
+        let merge_request = match Store::get(&key(&type_name, "mergerequest")) {
+            Ok(value) => value.trim() != "false",
+            Err(_) => true
+        };
+
+        Ok(CustomBranch {
+            name: type_name,
+            prefix,
+            base,
+            targets,
+            strategy,
+            merge_request,
+        })
+
+    }
+
+    ///
+    /// Loads every custom branch type declared in the store,
+    /// misconfigured ones included (as errors).
+    ///
+    pub fn declared () -> Vec<Result<CustomBranch, String>> {
+
+        let pattern = format!(r"^{}\..*\.prefix$", BRANCH_TYPE_KEY_PREFIX.replace('.', r"\."));
+
+        Store::get_regexp(&pattern)
+            .iter()
+            .filter_map(|(key, _)| {
+                key
+                    .strip_prefix(&format!("{}.", BRANCH_TYPE_KEY_PREFIX))
+                    .and_then(|rest| rest.strip_suffix(".prefix"))
+                    .map(|type_name| type_name.to_string())
+            })
+            .map(|type_name| CustomBranch::load(&type_name))
+            .collect()
+
+    }
+
+    ///
+    /// Lists every custom branch type declared in the store.
+    /// Misconfigured types are left out.
+    ///
+    pub fn all () -> Vec<CustomBranch> {
+        CustomBranch::declared().into_iter().filter_map(|custom| custom.ok()).collect()
+    }
+
+    ///
+    /// Finds the custom branch type using the given prefix.
+    ///
+    pub fn from_prefix (prefix: &str) -> Option<CustomBranch> {
+        CustomBranch::all()
+            .into_iter()
+            .find(|custom| custom.prefix == prefix)
+    }
+
+}
@@ -0,0 +1,865 @@
+use std::path::PathBuf;
+
+use crate::command::browser::Browser;
+use crate::command::gitv2::GitV2;
+use crate::config::constants::FINISH_STATE_FILE;
+use crate::flow::branch::Branch;
+use crate::flow::changelog::Changelog;
+use crate::flow::hook::{Hook, HookTime};
+use crate::flow::preflight::Preflight;
+use crate::flow::strategy::MergeStrategy;
+use crate::flow::tracker::IssueTracker;
+use crate::flow::transaction::Step;
+use crate::flow::version::Version;
+use crate::{info, error, success, working, warning};
+
+///
+/// The progress of a finish: the steps done so far, to be
+/// undone if a later one fails. It is persisted under
+/// `.git/labflow/` when a merge stops on conflicts, so the
+/// finish can be continued (or aborted) later.
+///
+#[derive(Debug, PartialEq, Clone)]
+pub struct FinishState {
+    /// Full name of the branch being finished
+    pub branch: String,
+    /// What was checked out before the finish started
+    pub original_head: String,
+    pub strategy: MergeStrategy,
+    /// Targets still to be merged; the first one is in progress
+    pub targets: Vec<String>,
+    /// Targets already merged
+    pub merged: Vec<String>,
+    /// Side effects done so far, in order
+    pub steps: Vec<Step>,
+    /// Message of the tags created on main (or support)
+    pub tag_message: Option<String>,
+    /// Merge requests opened: their target and URL
+    pub merge_requests: Vec<(String, String)>,
+    /// The uncommitted changes stashed by `--autostash` (the stash
+    /// commit), given back once the finish is over
+    pub stash: Option<String>,
+}
+
+fn escape (text: &str) -> String {
+    text.replace('\\', "\\\\").replace('\n', "\\n")
+}
+
+fn unescape (text: &str) -> String {
+
+    let mut unescaped = String::new();
+    let mut chars = text.chars();
+
+    while let Some(current) = chars.next() {
+        match (current, chars.clone().next()) {
+            ('\\', Some('n')) => { unescaped.push('\n'); chars.next(); },
+            ('\\', Some('\\')) => { unescaped.push('\\'); chars.next(); },
+            _ => unescaped.push(current)
+        }
+    }
+
+    unescaped
+
+}
+
+impl FinishState {
+
+    ///
+    /// Starts the progress of a finish, remembering what is
+    /// checked out to go back to it.
+    ///
+    pub fn new (branch: &Branch, targets: &[Branch]) -> Result<FinishState, String> {
+
+        let original_head = match GitV2::current_branch() {
+            Some(head) => head,
+            None => GitV2::rev_parse("HEAD")?
+        };
+
+        Ok(FinishState {
+            branch: branch.full_name(),
+            original_head,
+            strategy: branch.merge_strategy(),
+            targets: targets.iter().map(|target| target.full_name()).collect(),
+            merged: Vec::new(),
+            steps: Vec::new(),
+            tag_message: None,
+            merge_requests: Vec::new(),
+            stash: None,
+        })
+
+    }
+
+    fn path () -> Result<PathBuf, String> {
+        Ok(PathBuf::from(GitV2::git_dir()?).join("labflow").join(FINISH_STATE_FILE))
+    }
+
+    ///
+    /// The targets of the merge requests opened.
+    ///
+    pub fn merge_request_targets (&self) -> Vec<String> {
+        self.merge_requests.iter().map(|(target, _)| target.clone()).collect()
+    }
+
+    ///
+    /// Tells if there is a finish waiting to be continued.
+    ///
+    pub fn exists () -> bool {
+        match FinishState::path() {
+            Ok(path) => path.exists(),
+            Err(_) => false
+        }
+    }
+
+    ///
+    /// Writes the progress as `key=value` lines.
+    ///
+    pub fn save (&self) -> Result<(), String> {
+
+        let mut lines: Vec<String> = vec![
+            format!("branch={}", self.branch),
+            format!("head={}", self.original_head),
+            format!("strategy={}", self.strategy),
+        ];
+
+        lines.extend(self.targets.iter().map(|target| format!("target={}", target)));
+        lines.extend(self.merged.iter().map(|merged| format!("merged={}", merged)));
+        lines.extend(self.steps.iter().map(|step| format!("step={}", step)));
+
+        lines.extend(self.merge_requests.iter().map(|(target, url)| format!("request={} {}", target, url)));
+
+        if let Some(tag_message) = &self.tag_message {
+            lines.push(format!("message={}", escape(tag_message)));
+        }
+
+        if let Some(stash) = &self.stash {
+            lines.push(format!("stash={}", stash));
+        }
+
+        let path = FinishState::path()?;
+
+        if let Some(dir) = path.parent() {
+            if let Err(e) = std::fs::create_dir_all(dir) {
+                return Err(format!("Couldn't create {}: {}", dir.display(), e));
+            }
+        }
+
+        match std::fs::write(&path, lines.join("\n") + "\n") {
+            Ok(_) => Ok(()),
+            Err(e) => Err(format!("Couldn't save the finish progress in {}: {}", path.display(), e))
+        }
+
+    }
+
+    ///
+    /// Reads the progress saved by a finish stopped on conflicts.
+    ///
+    pub fn load () -> Result<FinishState, String> {
+
+        let path = FinishState::path()?;
+
+        let content = match std::fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(_) => return Err(String::from("There is no finish in progress."))
+        };
+
+        let mut state = FinishState {
+            branch: String::new(),
+            original_head: String::new(),
+            strategy: MergeStrategy::default_strategy(),
+            targets: Vec::new(),
+            merged: Vec::new(),
+            steps: Vec::new(),
+            tag_message: None,
+            merge_requests: Vec::new(),
+            stash: None,
+        };
+
+        for line in content.lines() {
+
+            let (key, value) = match line.split_once('=') {
+                Some(pair) => pair,
+                None => continue
+            };
+
+            match key {
+                "branch" => state.branch = value.to_string(),
+                "head" => state.original_head = value.to_string(),
+                "strategy" => if let Some(strategy) = MergeStrategy::parse(value) { state.strategy = strategy },
+                "target" => state.targets.push(value.to_string()),
+                "merged" => state.merged.push(value.to_string()),
+                "step" => match Step::parse(value) {
+                    Some(step) => state.steps.push(step),
+                    None => return Err(format!("Unknown step '{}' in the finish progress.", value))
+                },
+                "request" => if let Some((target, url)) = value.split_once(' ') { state.merge_requests.push((target.to_string(), url.to_string())) },
+                "message" => state.tag_message = Some(unescape(value)),
+                "stash" => state.stash = Some(value.to_string()),
+                _ => {}
+            }
+
+        }
+
+        if state.branch.is_empty() {
+            return Err(format!("The finish progress in {} is corrupted.", path.display()));
+        }
+
+        Ok(state)
+
+    }
+
+    ///
+    /// Forgets the progress (the finish is over).
+    ///
+    pub fn clear () -> Result<(), String> {
+
+        let path = FinishState::path()?;
+
+        if !path.exists() {
+            return Ok(());
+        }
+
+        match std::fs::remove_file(&path) {
+            Ok(_) => Ok(()),
+            Err(e) => Err(format!("Couldn't remove {}: {}", path.display(), e))
+        }
+
+    }
+
+}
+
+///
+/// Flags of `git lab finish`.
+///
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct FinishOptions {
+    /// Removes the branch even if its commits aren't merged nor pushed
+    pub force: bool,
+    /// Stashes the uncommitted changes during the finish
+    pub autostash: bool,
+    /// Keeps the remote branch of a branch merged locally
+    pub keep_remote: bool,
+}
+
+pub enum Finish {}
+
+impl Finish {
+
+    ///
+    /// Finishes the given branch: opens merge requests when there
+    /// is a remote server or merges it locally into its targets,
+    /// then removes the local branch. If any step fails, the ones
+    /// already done are undone.
+    ///
+    pub fn run (branch: &Branch, options: FinishOptions) -> Result<(), String> {
+
+        if let Branch::Support(_) = branch {
+            return Err(String::from("Support branches are long-lived: they can't be finished."));
+        }
+
+        if FinishState::exists() {
+            return Err(String::from("There is a finish in progress: use `git lab finish --continue` or `git lab finish --abort`."));
+        }
+
+        let targets = branch.source()?;
+        let target_names: Vec<String> = targets.iter().map(|target| target.full_name()).collect();
+
+        let finish_hook = Hook::new("finish", branch, &branch.base()?, &target_names);
+
+        finish_hook.run(HookTime::Pre)?;
+
+        let mut state = FinishState::new(branch, &targets)?;
+
+        Finish::transaction(&mut state, |state| Finish::steps(branch, &targets, options, state))?;
+
+        if GitV2::is_remote() {
+            Finish::pull_targets(&target_names);
+        }
+
+        IssueTracker::finished(branch, &state.merge_request_targets());
+
+        finish_hook.run(HookTime::Post)
+
+    }
+
+    ///
+    /// Brings the targets up to date with the remote, once the
+    /// finish is over: they are fast-forwarded when behind. The
+    /// remote-tracking branches deleted on the remote are pruned.
+    /// Nothing here fails the finish, it is only reported.
+    ///
+    pub fn pull_targets (targets: &[String]) {
+
+        if let Err(e) = GitV2::fetch_prune() {
+            warning!("Couldn't fetch from the remote, the targets weren't updated: {}", e.trim());
+            return;
+        }
+
+        for target in targets.iter() {
+
+            let remote_target = format!("origin/{}", target);
+
+            let (local_commit, remote_commit) = match (GitV2::rev_parse(target), GitV2::rev_parse(&remote_target)) {
+                (Ok(local_commit), Ok(remote_commit)) => (local_commit, remote_commit),
+                _ => continue
+            };
+
+            if local_commit == remote_commit {
+                info!("{} is up to date with {}", target, &remote_target);
+                continue;
+            }
+
+            if GitV2::is_ancestor(&remote_commit, &local_commit) {
+                let ahead = GitV2::count_commits(&remote_commit, &local_commit).unwrap_or_default();
+                info!("{} is {} commit(s) ahead of {}", target, ahead, &remote_target);
+                continue;
+            }
+
+            if !GitV2::is_ancestor(&local_commit, &remote_commit) {
+                warning!("{} has diverged from {}: it wasn't updated", target, &remote_target);
+                continue;
+            }
+
+            let behind = GitV2::count_commits(&local_commit, &remote_commit).unwrap_or_default();
+
+            let result = match GitV2::current_branch() {
+                Some(current) if &current == target => GitV2::fast_forward(&remote_target),
+                _ => GitV2::update_ref(&format!("refs/heads/{}", target), &remote_commit)
+            };
+
+            match result {
+                Ok(_) => { success!("{} moved {} commit(s) forward to {}", target, behind, &remote_target); },
+                Err(e) => { warning!("Couldn't update {}: {}", target, e.trim()); }
+            }
+
+        }
+
+    }
+
+    fn steps (branch: &Branch, targets: &[Branch], options: FinishOptions, state: &mut FinishState) -> Result<(), String> {
+
+        let branch_fullname = branch.full_name();
+
+        // 0. Releases get a changelog: in the file, the merge request and the tag
+        let changelog: Option<Changelog> = match branch {
+            Branch::Release(_) => {
+                GitV2::checkout(None, &branch_fullname, false)?;
+                let before = GitV2::rev_parse(&branch_fullname)?;
+                let changelog = Changelog::generate(branch)?;
+                changelog.prepend()?;
+                state.steps.push(Step::Commit { branch: branch_fullname.clone(), before });
+                Some(changelog)
+            },
+            _ => None
+        };
+
+        let changelog_section: Option<&str> = changelog.as_ref().map(|changelog| changelog.section.as_str());
+
+        let opens_merge_requests = GitV2::is_remote() && branch.opens_merge_request();
+
+        // 1. With a remote (and a branch type that wants it), merge requests are opened
+        if opens_merge_requests {
+
+            let target_names: Vec<String> = targets.iter().map(|target| target.full_name()).collect();
+
+            let publish_hook = Hook::new("publish", branch, &branch.base()?, &target_names);
+
+            publish_hook.run(HookTime::Pre)?;
+
+            let before = GitV2::remote_commit(&branch_fullname);
+
+            GitV2::push(&branch_fullname, before.is_none())?;
+
+            state.steps.push(Step::Push { branch: branch_fullname.clone(), before });
+
+            success!("Branch {} pushed to remote!", &branch_fullname);
+
+            publish_hook.run(HookTime::Post)?;
+
+            targets.iter().for_each(|target| {
+                match Browser::merge_request(branch, target, changelog_section) {
+                    Ok(url) => {
+                        state.steps.push(Step::MergeRequest { branch: branch_fullname.clone(), target: target.full_name() });
+                        state.merge_requests.push((target.full_name(), url));
+                    },
+                    Err(e) => { error!("Something weird while opening merge request 🫣: {}", e); }
+                }
+            });
+
+        } else {
+
+            // 2. Without one, the branch is merged locally into its targets
+            state.tag_message = changelog_section.map(|section| {
+                format!("Tag {} from branch {}\n\n{}", Version::tag_name(branch.name()), &branch_fullname, section)
+            });
+
+            Finish::merge_targets(branch, state)?;
+
+        }
+
+        let target_names: Vec<String> = targets.iter().map(|target| target.full_name()).collect();
+
+        Finish::conclude(branch, &target_names, options, state)?;
+
+        // 3. Merged here, the branch pushed by the start isn't needed on the remote anymore
+        if !opens_merge_requests && !options.keep_remote {
+            Finish::clean_remote(&branch_fullname, &target_names, state);
+        }
+
+        Ok(())
+
+    }
+
+    ///
+    /// Continues the finish stopped on conflicts, once they are
+    /// resolved (and staged).
+    ///
+    pub fn resume (options: FinishOptions) -> Result<(), String> {
+
+        let mut state = FinishState::load()?;
+
+        let branch = match Branch::from(&state.branch) {
+            Some(branch) => branch,
+            None => return Err(format!("Unknown branch '{}' in the finish progress.", state.branch))
+        };
+
+        let unmerged_files = GitV2::unmerged_files()?;
+
+        if !unmerged_files.is_empty() {
+            return Err(format!(
+                "There are still conflicts in: {}. Resolve them and `git add` the files before continuing.",
+                unmerged_files.join(", ")
+            ));
+        }
+
+        let target = match state.targets.first() {
+            Some(target) => target.clone(),
+            None => return Err(String::from("The finish progress has no target left to merge."))
+        };
+
+        let result = Finish::transaction(&mut state, |state| {
+
+            working!("Concluding the merge of {} into {}", &state.branch, &target);
+
+            // 1. Concludes the merge stopped on conflicts. The progress is
+            // kept until it is done: on failure, it can still be continued or aborted
+            Finish::conclude_merge(state, &target)?;
+
+            Finish::merged(&branch, state)?;
+
+            // It is saved again if the finish stops on conflicts once more
+            FinishState::clear()?;
+
+            let target_names: Vec<String> = state.merged.iter().chain(state.targets.iter()).cloned().collect();
+
+            // 2. ... and goes on with the other targets
+            Finish::merge_targets(&branch, state)?;
+
+            Finish::conclude(&branch, &target_names, options, state)?;
+
+            if !options.keep_remote {
+                Finish::clean_remote(&branch.full_name(), &target_names, state);
+            }
+
+            Ok(())
+
+        });
+
+        Finish::give_back_stash(&state);
+
+        result?;
+
+        if GitV2::is_remote() {
+            Finish::pull_targets(&state.merged);
+        }
+
+        IssueTracker::finished(&branch, &state.merge_request_targets());
+
+        Hook::new("finish", &branch, &branch.base()?, &state.merged).run(HookTime::Post)
+
+    }
+
+    ///
+    /// Concludes the merge into the target stopped on conflicts,
+    /// resolved since.
+    ///
+    fn conclude_merge (state: &FinishState, target: &str) -> Result<(), String> {
+
+        let kept = |e: String| format!(
+            "Couldn't conclude the merge of {} into {}: {}\nFix it and run `git lab finish --continue` again (or `git lab finish --abort` to give up).",
+            &state.branch,
+            target,
+            e.trim()
+        );
+
+        if GitV2::is_rebasing() {
+            let result = GitV2::rebase_continue();
+            Finish::stop_on_conflicts(state, target, result)?;
+            GitV2::merge_local(None, &state.branch, None, target, MergeStrategy::FastForward).map_err(kept)?;
+        } else if GitV2::is_merging() {
+            GitV2::merge_continue().map_err(kept)?;
+        } else if state.strategy == MergeStrategy::Squash && GitV2::has_staged_changes() {
+            GitV2::commit(GitV2::squash_message(&state.branch, target)?, false).map_err(kept)?;
+        }
+
+        Ok(())
+
+    }
+
+    ///
+    /// Gives up the finish stopped on conflicts, undoing
+    /// everything it did.
+    ///
+    pub fn abort () -> Result<(), String> {
+
+        let state = FinishState::load()?;
+
+        working!("Aborting the finish of {}", &state.branch);
+
+        Finish::rollback(&state)?;
+
+        Finish::give_back_stash(&state);
+
+        success!("Finish of {} aborted", &state.branch);
+
+        Ok(())
+
+    }
+
+    ///
+    /// Brings back the changes stashed by `--autostash` when the
+    /// finish started, once it is over (not stopped once more).
+    ///
+    fn give_back_stash (state: &FinishState) {
+
+        if FinishState::exists() {
+            return;
+        }
+
+        if let Some(stash) = &state.stash {
+            if let Err(e) = Preflight::pop_stash(stash) {
+                error!("{}", e);
+            }
+        }
+
+    }
+
+    ///
+    /// Runs the steps of a finish. When one of them fails, the
+    /// ones already done are undone, unless the finish stopped
+    /// on conflicts (it is saved to be continued or aborted).
+    ///
+    fn transaction<F> (state: &mut FinishState, steps: F) -> Result<(), String>
+    where
+        F: FnOnce(&mut FinishState) -> Result<(), String>
+    {
+
+        match steps(state) {
+
+            Ok(_) => FinishState::clear(),
+
+            Err(e) if FinishState::exists() => Err(e),
+
+            Err(e) => {
+
+                error!("{}", e);
+
+                working!("Undoing the finish of {}", &state.branch);
+
+                Finish::rollback(state)?;
+
+                Err(format!("Finish of {} failed, so what it had done was undone.", &state.branch))
+
+            }
+
+        }
+
+    }
+
+    ///
+    /// Undoes the steps done so far, from the last one, reporting
+    /// each of them, and goes back to what was checked out.
+    ///
+    fn rollback (state: &FinishState) -> Result<(), String> {
+
+        if GitV2::is_merging() || GitV2::is_rebasing() || !GitV2::unmerged_files()?.is_empty() || GitV2::has_staged_changes() {
+            GitV2::merge_abort()?;
+            info!("Undone: merge in progress aborted");
+        }
+
+        // Detached, no checked out branch is moved under our feet
+        GitV2::detach()?;
+
+        for step in state.steps.iter().rev() {
+            match step.undo() {
+                Ok(undone) => { info!("Undone: {}", undone); },
+                Err(e) => { error!("Not undone: {}", e); }
+            }
+        }
+
+        GitV2::checkout(None, &state.original_head, false)?;
+
+        info!("Undone: {} checked out again", &state.original_head);
+
+        FinishState::clear()
+
+    }
+
+    ///
+    /// Merges the branch into the targets left in the state. On
+    /// conflicts, the state is saved so the finish can go on later.
+    ///
+    fn merge_targets (branch: &Branch, state: &mut FinishState) -> Result<(), String> {
+
+        while let Some(target) = state.targets.first().cloned() {
+
+            let step = Step::Merge {
+                branch: state.branch.clone(),
+                branch_before: GitV2::rev_parse(&state.branch)?,
+                target: target.clone(),
+                target_before: GitV2::rev_parse(&target)?,
+            };
+
+            let result = GitV2::merge_local(None, &state.branch, None, &target, state.strategy);
+
+            // A merge that couldn't even start has nothing to undo
+            if result.is_ok() || GitV2::is_rebasing() || !GitV2::unmerged_files()?.is_empty() {
+                state.steps.push(step);
+            }
+
+            Finish::stop_on_conflicts(state, &target, result)?;
+
+            Finish::merged(branch, state)?;
+
+        }
+
+        Ok(())
+
+    }
+
+    ///
+    /// Saves the progress when the merge into the target stopped
+    /// on conflicts. Other errors are just given back.
+    ///
+    fn stop_on_conflicts (state: &FinishState, target: &str, result: Result<String, String>) -> Result<(), String> {
+
+        let e = match result {
+            Ok(_) => return Ok(()),
+            Err(e) => e
+        };
+
+        if GitV2::is_rebasing() || !GitV2::unmerged_files()?.is_empty() {
+
+            state.save()?;
+
+            return Err(format!(
+                "Conflicts merging {} into {}. Resolve them, `git add` the files and run `git lab finish --continue` (or `git lab finish --abort` to give up).",
+                &state.branch,
+                target
+            ));
+
+        }
+
+        Err(format!(
+            "Couldn't merge {} into {} ({} strategy): {}",
+            &state.branch,
+            target,
+            state.strategy,
+            e
+        ))
+
+    }
+
+    ///
+    /// Bookkeeping after the merge into the first target: it is
+    /// tagged if needed and moved to the merged ones.
+    ///
+    fn merged (branch: &Branch, state: &mut FinishState) -> Result<(), String> {
+
+        let target = state.targets.remove(0);
+
+        success!("Branch {} merged into {}", &state.branch, &target);
+
+        // Releases and hotfixes are tagged on main (or support)
+        if let Some(target_branch) = Branch::from(&target) {
+
+            if branch.is_tagged_on(&target_branch) {
+
+                let tag_name = Version::tag_name(branch.name());
+
+                let tag_message = match &state.tag_message {
+                    Some(message) => message.clone(),
+                    None => format!("Tag {} from branch {}", &tag_name, &state.branch)
+                };
+
+                let base = branch.base()?;
+                let mut tag_hook = Hook::new("tag", branch, &base, std::slice::from_ref(&target));
+                tag_hook.tag = Some(tag_name.clone());
+
+                tag_hook.run(HookTime::Pre)?;
+
+                match GitV2::tag(&tag_name, &tag_message, &target) {
+                    Ok(_) => {
+                        success!("Tag {} created on {}", &tag_name, &target);
+                        state.steps.push(Step::Tag { name: tag_name });
+                        tag_hook.run(HookTime::Post)?;
+                    },
+                    Err(e) => { error!("Something weird while tagging 🫣: {}", e); }
+                }
+
+            }
+
+        }
+
+        state.merged.push(target);
+
+        Ok(())
+
+    }
+
+    ///
+    /// The targets that don't have the work of the branch yet. None
+    /// of them, when the branch tip was pushed to its remote
+    /// counterpart.
+    ///
+    fn unmerged_targets (branch_fullname: &str, targets: &[String]) -> Result<Vec<String>, String> {
+
+        let tip = GitV2::rev_parse(branch_fullname)?;
+
+        if let Ok(remote_tip) = GitV2::rev_parse(&format!("origin/{}", branch_fullname)) {
+            if GitV2::is_ancestor(&tip, &remote_tip) {
+                return Ok(Vec::new());
+            }
+        }
+
+        Ok(
+            targets
+                .iter()
+                // Squashed branches have their changes, not their commits, in the target
+                .filter(|target| !GitV2::is_ancestor(&tip, target) && !GitV2::is_content_merged(&tip, target))
+                .cloned()
+                .collect()
+        )
+
+    }
+
+    ///
+    /// Goes back to the base branch and removes the finished one,
+    /// unless its commits would be lost (no `--force`).
+    ///
+    fn conclude (branch: &Branch, targets: &[String], options: FinishOptions, state: &mut FinishState) -> Result<(), String> {
+
+        let branch_fullname = branch.full_name();
+
+        GitV2::checkout(None, &branch.base()?, false)?;
+
+        if !options.force {
+
+            let unmerged_targets = Finish::unmerged_targets(&branch_fullname, targets)?;
+
+            if !unmerged_targets.is_empty() {
+                return Err(format!(
+                    "Branch {} was kept: its commits aren't in {} nor pushed. Use --force to remove it anyway.",
+                    &branch_fullname,
+                    unmerged_targets.join(", ")
+                ));
+            }
+
+        }
+
+        let commit = GitV2::rev_parse(&branch_fullname)?;
+        let base = branch.recorded_base();
+
+        // git's own check (-d) only looks at HEAD and the upstream, and
+        // the branch was already checked against every target above
+        if GitV2::remove_local_branch(None, &branch_fullname, false).is_err() {
+            GitV2::remove_local_branch(None, &branch_fullname, true)?;
+        }
+
+        state.steps.push(Step::Delete { branch: branch_fullname.clone(), commit, base });
+
+        success!("Branch {} removed from local", &branch_fullname);
+
+        Ok(())
+
+    }
+
+    ///
+    /// Removes the branch from the remote, if it is there and
+    /// everything pushed to it was merged into the targets, on the
+    /// remote too: until they are pushed, it is the only shared
+    /// copy of the work. Failing to do so doesn't fail the finish.
+    ///
+    fn clean_remote (branch_fullname: &str, targets: &[String], state: &mut FinishState) {
+
+        if !GitV2::is_remote() {
+            return;
+        }
+
+        let commit = match GitV2::remote_commit(branch_fullname) {
+            Some(commit) => commit,
+            None => return
+        };
+
+        // Someone may have pushed commits that weren't merged here
+        if targets.iter().any(|target| !GitV2::is_ancestor(&commit, target) && !GitV2::is_content_merged(&commit, target)) {
+            warning!("Branch {} was kept on the remote: it has commits that weren't merged", branch_fullname);
+            return;
+        }
+
+        let unpushed_targets: Vec<&str> = targets
+            .iter()
+            .filter(|target| {
+                let remote_target = format!("origin/{}", target);
+                GitV2::rev_parse(&remote_target).is_err()
+                    || (!GitV2::is_ancestor(&commit, &remote_target) && !GitV2::is_content_merged(&commit, &remote_target))
+            })
+            .map(|target| target.as_str())
+            .collect();
+
+        if !unpushed_targets.is_empty() {
+            warning!(
+                "Branch {} was kept on the remote: its commits aren't on the remote {} yet. Push {}, then remove it with `git push origin --delete {}`",
+                branch_fullname,
+                unpushed_targets.join(", "),
+                unpushed_targets.join(" and "),
+                branch_fullname
+            );
+            return;
+        }
+
+        match GitV2::remove_remote_branch(branch_fullname) {
+            Ok(_) => {
+                success!("Branch {} removed from the remote", branch_fullname);
+                state.steps.push(Step::RemoteDelete { branch: branch_fullname.to_string(), commit });
+            },
+            Err(e) => { warning!("Couldn't remove {} from the remote: {}", branch_fullname, e.trim()); }
+        }
+
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{escape, unescape};
+
+    #[test]
+    fn messages_are_kept_on_one_line () {
+
+        let message = "## 1.2.0\n\n- feat: a=b\n- fix: C:\\path\\n is not a newline\n";
+
+        assert!(!escape(message).contains('\n'));
+        assert_eq!(unescape(&escape(message)), message);
+
+    }
+
+    #[test]
+    fn escaped_separators_are_read_back () {
+        assert_eq!(escape("a\\nb\nc"), "a\\\\nb\\nc");
+        assert_eq!(unescape("a\\\\nb\\nc"), "a\\nb\nc");
+        assert_eq!(unescape("trailing\\"), "trailing\\");
+        assert_eq!(unescape(&escape("key=value\\")), "key=value\\");
+    }
+
+}
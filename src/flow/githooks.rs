@@ -0,0 +1,242 @@
+use std::io::BufRead;
+use std::path::PathBuf;
+
+use crate::command::gitv2::GitV2;
+use crate::config::constants::{
+    ALLOW_PROTECTED_ENV,
+    DEVELOP_BRANCH_NAME_KEY,
+    MAIN_BRANCH_NAME_KEY,
+};
+use crate::config::store::Store;
+use crate::flow::branch::Branch;
+use crate::flow::lint::Lint;
+use crate::flow::policy::NamingPolicy;
+use crate::{info, success};
+
+/// Marks the hooks written by lab-flow, so only those are uninstalled
+const HOOK_MARKER: &str = "# Installed by lab-flow (git lab hooks install)";
+
+/// The git hooks lab-flow installs
+const INSTALLED_HOOKS: [&str; 4] = ["pre-commit", "pre-push", "prepare-commit-msg", "commit-msg"];
+
+///
+/// `git lab hooks`: the git hooks that keep the flow, calling
+/// back into lab-flow (`git lab hooks run <HOOK>`). They reject
+/// commits and pushes made straight to the main and develop
+/// branches, unless `LABFLOW_ALLOW_PROTECTED=1` is set (as
+/// lab-flow does for its own merges), start the commit
+/// messages with the issue key of the branch and, when
+/// `lab.flow.lint.enabled` is set, check they follow
+/// Conventional Commits.
+///
+pub enum GitHooks {}
+
+impl GitHooks {
+
+    ///
+    /// Where git looks for its hooks: `core.hooksPath` or `.git/hooks`.
+    ///
+    fn dir () -> Result<PathBuf, String> {
+        Ok(PathBuf::from(GitV2::hooks_dir()?))
+    }
+
+    pub fn install () -> Result<(), String> {
+
+        let dir = GitHooks::dir()?;
+
+        if let Err(e) = std::fs::create_dir_all(&dir) {
+            return Err(format!("Couldn't create {}: {}", dir.display(), e));
+        }
+
+        let executable = match std::env::current_exe() {
+            Ok(executable) => executable.to_string_lossy().to_string(),
+            Err(e) => return Err(format!("Couldn't find the lab-flow executable: {}", e))
+        };
+
+        for hook_name in INSTALLED_HOOKS.iter() {
+
+            let path = dir.join(hook_name);
+
+            // Someone else's hook is never overwritten
+            if let Ok(content) = std::fs::read_to_string(&path) {
+                if !content.contains(HOOK_MARKER) {
+                    return Err(format!("There is already a {} hook in {}: merge it by hand.", hook_name, dir.display()));
+                }
+            }
+
+            let script = format!(
+                "#!/bin/sh\n{}\nexec \"{}\" hooks run {} \"$@\"\n",
+                HOOK_MARKER,
+                executable,
+                hook_name
+            );
+
+            if let Err(e) = std::fs::write(&path, script) {
+                return Err(format!("Couldn't write {}: {}", path.display(), e));
+            }
+
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+
+                if let Err(e) = std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)) {
+                    return Err(format!("Couldn't make {} executable: {}", path.display(), e));
+                }
+            }
+
+            success!("Hook {} installed", path.display());
+
+        }
+
+        Ok(())
+
+    }
+
+    pub fn uninstall () -> Result<(), String> {
+
+        let dir = GitHooks::dir()?;
+
+        for hook_name in INSTALLED_HOOKS.iter() {
+
+            let path = dir.join(hook_name);
+
+            match std::fs::read_to_string(&path) {
+                Ok(content) if content.contains(HOOK_MARKER) => {
+                    if let Err(e) = std::fs::remove_file(&path) {
+                        return Err(format!("Couldn't remove {}: {}", path.display(), e));
+                    }
+                    success!("Hook {} uninstalled", path.display());
+                },
+                Ok(_) => { info!("Hook {} wasn't installed by lab-flow: it was kept", path.display()); },
+                Err(_) => {}
+            }
+
+        }
+
+        Ok(())
+
+    }
+
+    ///
+    /// The branches nobody commits (nor pushes) to directly.
+    ///
+    fn protected_branches () -> Vec<String> {
+        [MAIN_BRANCH_NAME_KEY, DEVELOP_BRANCH_NAME_KEY]
+            .iter()
+            .filter_map(|key| Store::get(key).ok())
+            .map(|name| String::from(name.trim()))
+            .collect()
+    }
+
+    fn is_allowed () -> bool {
+        matches!(std::env::var(ALLOW_PROTECTED_ENV).as_deref(), Ok("1") | Ok("true"))
+    }
+
+    ///
+    /// Runs the given git hook: an error rejects the commit (or push).
+    ///
+    pub fn run (hook_name: &str, args: &[String]) -> Result<(), String> {
+
+        let protected_branches = GitHooks::protected_branches();
+
+        match hook_name {
+
+            // The bypass is about protected branches only: messages are still checked
+            "pre-commit" | "pre-push" if GitHooks::is_allowed() => Ok(()),
+
+            "pre-commit" => match GitV2::current_branch() {
+                Some(branch) if protected_branches.contains(&branch) => Err(format!(
+                    "Commits straight to {} are not allowed: start a branch (git lab feature start ...) or set {}=1.",
+                    branch,
+                    ALLOW_PROTECTED_ENV
+                )),
+                _ => Ok(())
+            },
+
+            // Each line of stdin: <local ref> <local sha> <remote ref> <remote sha>
+            "pre-push" => {
+
+                for line in std::io::stdin().lock().lines().map_while(Result::ok) {
+
+                    let remote_ref = match line.split_whitespace().nth(2) {
+                        Some(remote_ref) => remote_ref.to_string(),
+                        None => continue
+                    };
+
+                    if let Some(branch) = protected_branches.iter().find(|branch| remote_ref == format!("refs/heads/{}", branch)) {
+                        return Err(format!(
+                            "Pushes straight to {} are not allowed: finish a branch (git lab <type> finish ...) or set {}=1.",
+                            branch,
+                            ALLOW_PROTECTED_ENV
+                        ));
+                    }
+
+                }
+
+                Ok(())
+
+            },
+
+            // Arguments: <message file> [<source> [<commit>]]
+            "prepare-commit-msg" => match args.first() {
+                Some(message_file) => GitHooks::prefix_issue_key(message_file, args.get(1).map(|source| source.as_str())),
+                None => Err(String::from("The prepare-commit-msg hook needs the message file"))
+            },
+
+            // Arguments: <message file>
+            "commit-msg" => match args.first() {
+                Some(message_file) => Lint::message_file(message_file),
+                None => Err(String::from("The commit-msg hook needs the message file"))
+            },
+
+            _ => Err(format!("Unknown hook '{}'", hook_name))
+
+        }
+
+    }
+
+    ///
+    /// Starts the commit message with the issue key of the current
+    /// branch (`feature/ABC-123-login` gives `ABC-123`), unless it
+    /// is already there. Merge, squash and fixup commits are left
+    /// as they are.
+    ///
+    fn prefix_issue_key (message_file: &str, source: Option<&str>) -> Result<(), String> {
+
+        if matches!(source, Some("merge") | Some("squash")) {
+            return Ok(());
+        }
+
+        let issue_key = match GitV2::current_branch().as_deref().and_then(Branch::from) {
+            Some(branch) => match NamingPolicy::issue_key(branch.name()) {
+                Some(issue_key) => issue_key,
+                None => return Ok(())
+            },
+            None => return Ok(())
+        };
+
+        let message = match std::fs::read_to_string(message_file) {
+            Ok(message) => message,
+            Err(e) => return Err(format!("Couldn't read {}: {}", message_file, e))
+        };
+
+        let subject = message.lines().find(|line| !line.starts_with('#')).unwrap_or_default();
+
+        if subject.contains(&issue_key) || ["fixup!", "squash!", "amend!"].iter().any(|prefix| subject.starts_with(prefix)) {
+            return Ok(());
+        }
+
+        let prefixed = match subject.is_empty() {
+            // No message yet: the key is where it will be typed
+            true => format!("{} {}", issue_key, message),
+            false => message.replacen(subject, &format!("{} {}", issue_key, subject), 1)
+        };
+
+        match std::fs::write(message_file, prefixed) {
+            Ok(_) => Ok(()),
+            Err(e) => Err(format!("Couldn't write {}: {}", message_file, e))
+        }
+
+    }
+
+}
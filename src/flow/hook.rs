@@ -0,0 +1,132 @@
+use std::path::{Path, PathBuf};
+
+use crate::command::gitv2::GitV2;
+use crate::command::runner::Runner;
+use crate::config::constants::HOOKS_PATH_KEY;
+use crate::config::store::Store;
+use crate::flow::branch::Branch;
+use crate::{info, warning};
+
+///
+/// When a hook runs: before the action (it may stop it)
+/// or after it.
+///
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum HookTime {
+    Pre,
+    Post,
+}
+
+///
+/// The scripts run around the flow actions, named like the
+/// git-flow ones: `pre-flow-feature-start`, `post-flow-release-finish`,
+/// `pre-flow-hotfix-tag`, ... They are looked for in `lab.flow.hooks.path`
+/// (relative to the repository root) or in `.git/hooks`.
+///
+/// They get the branch name, its base and its targets as arguments,
+/// and the same (plus the type, the full branch name, the action and
+/// the tag, if any) in `LABFLOW_*` environment variables.
+///
+pub struct Hook<'a> {
+    pub action: &'a str,
+    pub branch: &'a Branch,
+    pub base: String,
+    pub targets: Vec<String>,
+    pub tag: Option<String>,
+}
+
+fn is_executable (path: &Path) -> bool {
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+
+        match path.metadata() {
+            Ok(metadata) => metadata.is_file() && metadata.permissions().mode() & 0o111 != 0,
+            Err(_) => false
+        }
+    }
+
+    #[cfg(not(unix))]
+    {
+        path.is_file()
+    }
+
+}
+
+impl<'a> Hook<'a> {
+
+    pub fn new (action: &'a str, branch: &'a Branch, base: &str, targets: &[String]) -> Hook<'a> {
+        Hook {
+            action,
+            branch,
+            base: base.to_string(),
+            targets: targets.to_vec(),
+            tag: None,
+        }
+    }
+
+    fn dir () -> Result<PathBuf, String> {
+        match Store::get(HOOKS_PATH_KEY) {
+            Ok(hooks_path) => Ok(PathBuf::from(GitV2::root_dir()?).join(hooks_path.trim())),
+            Err(_) => Ok(PathBuf::from(GitV2::git_dir()?).join("hooks"))
+        }
+    }
+
+    fn name (&self, time: HookTime) -> String {
+        let time = match time {
+            HookTime::Pre => "pre",
+            HookTime::Post => "post",
+        };
+        format!("{}-flow-{}-{}", time, self.branch.type_name(), self.action)
+    }
+
+    ///
+    /// Runs the hook, if there is one. A pre hook exiting with
+    /// an error stops the action; a post hook only warns.
+    ///
+    pub fn run (&self, time: HookTime) -> Result<(), String> {
+
+        let hook_name = self.name(time);
+
+        let path = Hook::dir()?.join(&hook_name);
+
+        if !is_executable(&path) {
+            return Ok(());
+        }
+
+        info!("Running the {} hook", &hook_name);
+
+        let mut args: Vec<String> = vec![self.branch.name().to_string(), self.base.clone()];
+        args.extend(self.targets.iter().cloned());
+
+        let mut envs: Vec<(String, String)> = vec![
+            (String::from("LABFLOW_ACTION"), self.action.to_string()),
+            (String::from("LABFLOW_TYPE"), self.branch.type_name()),
+            (String::from("LABFLOW_NAME"), self.branch.name().to_string()),
+            (String::from("LABFLOW_BRANCH"), self.branch.full_name()),
+            (String::from("LABFLOW_BASE"), self.base.clone()),
+            (String::from("LABFLOW_TARGETS"), self.targets.join(" ")),
+        ];
+
+        if let Some(tag) = &self.tag {
+            envs.push((String::from("LABFLOW_TAG"), tag.clone()));
+        }
+
+        let failure = match Runner::run_with_env(&path.to_string_lossy(), &args, &envs) {
+            Ok(0) => return Ok(()),
+            Ok(code) => format!("exited with code {}", code),
+            Err(e) => format!("couldn't run: {}", e)
+        };
+
+        match time {
+            HookTime::Pre => Err(format!("The {} hook {}, so {} was stopped.", &hook_name, failure, self.action)),
+            HookTime::Post => {
+                warning!("The {} hook {}", &hook_name, failure);
+                Ok(())
+            }
+        }
+
+    }
+
+}
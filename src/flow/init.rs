@@ -2,6 +2,7 @@ use regex::Regex;
 use crate::config::constants::BRANCH_ALREADY_EXISTS_SUFFIX;
 use crate::config::store::{Store};
 use crate::command::gitv2::{GitV2};
+use crate::flow::custom::CustomBranch;
 
 use crate::config::constants::{
     FEATURE_BRANCH_NAME_KEY,
@@ -10,8 +11,10 @@ use crate::config::constants::{
     RELEASE_BRANCH_NAME_KEY,
     DEVELOP_BRANCH_NAME_KEY,
     MAIN_BRANCH_NAME_KEY,
+    SUPPORT_BRANCH_NAME_KEY,
+    DEFAULT_SUPPORT_BRANCH_PREFIX,
 };
-use crate::{info, error};
+use crate::{info, warning, error};
 
 pub enum Script {}
 
@@ -96,12 +99,31 @@ pub fn show () {
         Err(_) => String::from("[NOT FOUND]")
     };
 
+    let support_branch_name = match Store::get(SUPPORT_BRANCH_NAME_KEY) {
+        Ok(value) => String::from(value.trim()),
+        Err(_) => String::from(DEFAULT_SUPPORT_BRANCH_PREFIX)
+    };
+
     info!("The feature branch prefix is {}", feature_branch_name);
     info!("The bugfix branch prefix is {}", bugfix_branch_name);
     info!("The hotfix branch prefix is {}", hotfix_branch_name);
     info!("The release branch prefix is {}", release_branch_name);
     info!("The develop branch name is {}", develop_branch_name);
     info!("The main branch name is {}", main_branch_name);
+    info!("The support branch prefix is {}", support_branch_name);
+
+    CustomBranch::declared().iter().for_each(|custom| match custom {
+        Ok(custom) => info!(
+            "The {} branch prefix is {} (from {}, finishing into {}, {} strategy{})",
+            custom.name,
+            custom.prefix,
+            custom.base,
+            custom.targets.join(", "),
+            custom.strategy,
+            if custom.merge_request { ", with merge request" } else { "" }
+        ),
+        Err(e) => warning!("{}", e)
+    });
 
 }
 
@@ -160,6 +182,17 @@ pub fn create () -> Result<(), Box<dyn std::error::Error>> {
     // 9. Adding the release branch name to the list of used names.
     used_names.push(release_branch_name.clone());
 
+    // 9.1. Reading the support branch name.
+    let support_branch_name = read_branch_name(
+        "Enter the prefix of the support branches (support/):",
+        DEFAULT_SUPPORT_BRANCH_PREFIX,
+        true,
+        &used_names
+    )?;
+
+    // 9.2. Adding the support branch name to the list of used names.
+    used_names.push(support_branch_name.clone());
+
     // 10. Reading the develop branch name.
     let develop_branch_name = read_branch_name(
         "Enter the name of the develop branch (develop):",
@@ -212,6 +245,7 @@ pub fn create () -> Result<(), Box<dyn std::error::Error>> {
         &bugfix_branch_name,
         &hotfix_branch_name,
         &release_branch_name,
+        &support_branch_name,
         &develop_branch_name,
         &main_branch_name
     ) {
@@ -458,6 +492,7 @@ fn store_branch_names (
     bugfix_branch_name: &str,
     hotfix_branch_name: &str,
     release_branch_name: &str,
+    support_branch_name: &str,
     develop_branch_name: &str,
     main_branch_name: &str
 ) -> Result<(), Box<InitError>> {
@@ -486,6 +521,12 @@ fn store_branch_names (
         Err(e) => return Err(Box::new(InitError::new(e)))
     }
 
+    // 4.1. Storing the support branch "prefix".
+    match Store::add(SUPPORT_BRANCH_NAME_KEY, support_branch_name.trim()) {
+        Ok(_) => {},
+        Err(e) => return Err(Box::new(InitError::new(e)))
+    }
+
     // 5. Storing the develop branch name.
     match Store::add(DEVELOP_BRANCH_NAME_KEY, develop_branch_name.trim()) {
         Ok(_) => {},
@@ -0,0 +1,286 @@
+use regex::Regex;
+
+use crate::command::gitv2::{GitV2, Commit};
+use crate::config::constants::{
+    LINT_KEY_PREFIX,
+    DEFAULT_COMMIT_TYPES,
+    DEFAULT_COMMIT_SUBJECT_MAX_LENGTH,
+    CONVENTIONAL_COMMIT_REGEX_PATTERN,
+};
+use crate::config::store::Store;
+use crate::flow::branch::Branch;
+use crate::flow::policy::NamingPolicy;
+use crate::{error, info, success};
+
+/// Commits nobody wrote by hand: they are never linted
+const SKIPPED_SUBJECT_PREFIXES: [&str; 8] = [
+    "Merge ",
+    "fixup!",
+    "squash!",
+    "amend!",
+    "Revert \"",
+    "Bump version to ",
+    "Update changelog for ",
+    "Squash branch ",
+];
+
+///
+/// The Conventional Commits rules, read from the git config
+/// of the repository:
+///
+/// ```text
+/// git config --local lab.flow.lint.enabled true
+/// git config --local lab.flow.lint.types feat,fix,docs,chore
+/// git config --local lab.flow.lint.scopes api,ui
+/// git config --local lab.flow.lint.scoperequired true
+/// git config --local lab.flow.lint.maxlength 72
+/// git config --local lab.flow.lint.breakingfooter true
+/// ```
+///
+/// `enabled` makes finish (and the commit-msg hook) check the
+/// commits of feature and bugfix branches; `git lab lint`
+/// always checks.
+///
+#[derive(Debug, PartialEq, Clone)]
+pub struct LintRules {
+    pub types: Vec<String>,
+    /// Empty: any scope
+    pub scopes: Vec<String>,
+    pub scope_required: bool,
+    pub max_length: usize,
+    /// Breaking changes (`feat!:`) must be explained in a `BREAKING CHANGE:` footer
+    pub breaking_footer: bool,
+}
+
+fn key (rule: &str) -> String {
+    format!("{}.{}", LINT_KEY_PREFIX, rule)
+}
+
+fn list (text: &str) -> Vec<String> {
+    text
+        .split(',')
+        .map(|item| item.trim().to_string())
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+impl LintRules {
+
+    pub fn load () -> LintRules {
+
+        let rule = |name: &str| match Store::get(&key(name)) {
+            Ok(value) if !value.trim().is_empty() => Some(String::from(value.trim())),
+            _ => None
+        };
+
+        LintRules {
+            types: list(&rule("types").unwrap_or_else(|| DEFAULT_COMMIT_TYPES.to_string())),
+            scopes: list(&rule("scopes").unwrap_or_default()),
+            scope_required: rule("scoperequired").as_deref() == Some("true"),
+            max_length: rule("maxlength")
+                .and_then(|value| value.parse::<usize>().ok())
+                .unwrap_or(DEFAULT_COMMIT_SUBJECT_MAX_LENGTH),
+            breaking_footer: rule("breakingfooter").as_deref() == Some("true"),
+        }
+
+    }
+
+    ///
+    /// Tells if the commits are checked on finish and by the
+    /// commit-msg hook.
+    ///
+    pub fn is_enabled () -> bool {
+        matches!(Store::get(&key("enabled")), Ok(value) if value.trim() == "true")
+    }
+
+    ///
+    /// Tells if the commits of the given branch must follow the rules.
+    ///
+    pub fn applies_to (branch: &Branch) -> bool {
+        matches!(branch, Branch::Feature(_) | Branch::Bugfix(_))
+    }
+
+    ///
+    /// Checks the commit message, telling every rule it breaks.
+    /// An issue key starting the subject (`ABC-123 feat: ...`)
+    /// is left out of the check.
+    ///
+    pub fn check (&self, subject: &str, body: &str) -> Result<Vec<String>, String> {
+
+        if SKIPPED_SUBJECT_PREFIXES.iter().any(|prefix| subject.starts_with(prefix)) {
+            return Ok(Vec::new());
+        }
+
+        let regex = match Regex::new(CONVENTIONAL_COMMIT_REGEX_PATTERN) {
+            Ok(regex) => regex,
+            Err(e) => return Err(e.to_string())
+        };
+
+        let subject = match NamingPolicy::issue_key(subject) {
+            Some(issue_key) => subject.strip_prefix(&format!("{} ", issue_key)).unwrap_or(subject),
+            None => subject
+        };
+
+        let mut broken_rules: Vec<String> = Vec::new();
+
+        if subject.chars().count() > self.max_length {
+            broken_rules.push(format!("the subject has more than {} characters", self.max_length));
+        }
+
+        let captures = match regex.captures(subject) {
+            Some(captures) => captures,
+            None => {
+                broken_rules.push(String::from("the subject isn't like `type(scope): description`"));
+                return Ok(broken_rules);
+            }
+        };
+
+        let commit_type = captures["type"].to_string();
+
+        if !self.types.contains(&commit_type) {
+            broken_rules.push(format!("the type '{}' isn't one of {}", commit_type, self.types.join(", ")));
+        }
+
+        match captures.name("scope") {
+            Some(scope) if !self.scopes.is_empty() && !self.scopes.iter().any(|allowed| allowed == scope.as_str()) => {
+                broken_rules.push(format!("the scope '{}' isn't one of {}", scope.as_str(), self.scopes.join(", ")));
+            },
+            None if self.scope_required => {
+                broken_rules.push(String::from("there is no scope"));
+            },
+            _ => {}
+        }
+
+        let breaking_footers: Vec<&str> = body
+            .lines()
+            .filter(|line| line.starts_with("BREAKING CHANGE") || line.starts_with("BREAKING-CHANGE"))
+            .collect();
+
+        if breaking_footers.iter().any(|footer| !footer.contains(": ") || footer.split_once(": ").map(|(_, text)| text.trim().is_empty()).unwrap_or(true)) {
+            broken_rules.push(String::from("the breaking change footer isn't like `BREAKING CHANGE: description`"));
+        }
+
+        if self.breaking_footer && captures.name("breaking").is_some() && breaking_footers.is_empty() {
+            broken_rules.push(String::from("the breaking change (!) isn't explained in a `BREAKING CHANGE:` footer"));
+        }
+
+        Ok(broken_rules)
+
+    }
+
+}
+
+///
+/// `git lab lint`: checks the commits of a branch against the
+/// Conventional Commits rules.
+///
+pub enum Lint {}
+
+impl Lint {
+
+    ///
+    /// The commits of the branch (only there, not in any other
+    /// branch) breaking the rules, with the rules they break.
+    ///
+    pub fn bad_commits (branch_fullname: &str) -> Result<Vec<(Commit, Vec<String>)>, String> {
+
+        let rules = LintRules::load();
+
+        let mut bad_commits: Vec<(Commit, Vec<String>)> = Vec::new();
+
+        for hash in GitV2::exclusive_commits(None, branch_fullname)? {
+
+            let commit = GitV2::read_commit(&hash)?;
+
+            if commit.is_merge() {
+                continue;
+            }
+
+            let broken_rules = rules.check(&commit.subject, &commit.body)?;
+
+            if !broken_rules.is_empty() {
+                bad_commits.push((commit, broken_rules));
+            }
+
+        }
+
+        Ok(bad_commits)
+
+    }
+
+    ///
+    /// Describes each bad commit and the rules it breaks.
+    ///
+    pub fn report (bad_commits: &[(Commit, Vec<String>)]) -> Vec<String> {
+        bad_commits
+            .iter()
+            .map(|(commit, broken_rules)| format!("{} {}: {}", commit.short_hash(), commit.subject, broken_rules.join("; ")))
+            .collect()
+    }
+
+    ///
+    /// Lints the given branch (or the checked out one).
+    ///
+    pub fn run (branch_fullname: Option<&str>) -> Result<(), String> {
+
+        let branch_fullname = match branch_fullname {
+            Some(branch_fullname) => branch_fullname.to_string(),
+            None => match GitV2::current_branch() {
+                Some(current) => current,
+                None => return Err(String::from("HEAD is detached: tell which branch to lint."))
+            }
+        };
+
+        info!("Linting the commits of {}", &branch_fullname);
+
+        let bad_commits = Lint::bad_commits(&branch_fullname)?;
+
+        if bad_commits.is_empty() {
+            success!("Every commit of {} follows Conventional Commits", &branch_fullname);
+            return Ok(());
+        }
+
+        Lint::report(&bad_commits)
+            .iter()
+            .for_each(|line| error!("{}", line));
+
+        Err(format!("{} commit(s) of {} don't follow Conventional Commits", bad_commits.len(), &branch_fullname))
+
+    }
+
+    ///
+    /// Lints the message being committed (the commit-msg hook), when
+    /// linting is enabled and the checked out branch must follow it.
+    ///
+    pub fn message_file (message_file: &str) -> Result<(), String> {
+
+        if !LintRules::is_enabled() {
+            return Ok(());
+        }
+
+        match GitV2::current_branch().as_deref().and_then(Branch::from) {
+            Some(branch) if LintRules::applies_to(&branch) => {},
+            _ => return Ok(())
+        }
+
+        let message = match std::fs::read_to_string(message_file) {
+            Ok(message) => message,
+            Err(e) => return Err(format!("Couldn't read {}: {}", message_file, e))
+        };
+
+        let lines: Vec<&str> = message.lines().filter(|line| !line.starts_with('#')).collect();
+
+        let subject = lines.first().copied().unwrap_or_default();
+        let body = lines.iter().skip(1).copied().collect::<Vec<&str>>().join("\n");
+
+        let broken_rules = LintRules::load().check(subject, &body)?;
+
+        if broken_rules.is_empty() {
+            return Ok(());
+        }
+
+        Err(format!("The commit message doesn't follow Conventional Commits: {}", broken_rules.join("; ")))
+
+    }
+
+}
@@ -0,0 +1,123 @@
+use crate::command::gitv2::{GitV2, Signature};
+use crate::command::http::Http;
+use crate::flow::status::FlowBranch;
+use crate::info;
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+///
+/// Filters and format of `git lab <type> list`.
+///
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct ListOptions {
+    /// Only the branches merged into all of their targets
+    pub merged: bool,
+    /// Only the branches with no commits for this many days
+    pub stale: Option<u32>,
+    /// Only the branches whose latest commit is from the user
+    pub mine: bool,
+    /// JSON, for scripts, instead of text
+    pub json: bool,
+}
+
+///
+/// A listed branch, with what is known about it.
+///
+struct Entry {
+    flow_branch: FlowBranch,
+    base: Option<String>,
+    last_commit: Option<Signature>,
+    pushed: bool,
+    merged: bool,
+}
+
+impl Entry {
+
+    fn json (&self) -> String {
+
+        let optional = |value: Option<&str>| match value {
+            Some(value) => Http::json_string(value),
+            None => String::from("null")
+        };
+
+        format!(
+            "{{\"name\":{},\"type\":{},\"base\":{},\"last_commit_date\":{},\"author\":{},\"author_email\":{},\"local\":{},\"remote\":{},\"pushed\":{},\"merged\":{}}}",
+            Http::json_string(&self.flow_branch.full_name),
+            Http::json_string(&self.flow_branch.branch.type_name()),
+            optional(self.base.as_deref()),
+            optional(self.last_commit.as_ref().map(|commit| commit.date.as_str())),
+            optional(self.last_commit.as_ref().map(|commit| commit.author.as_str())),
+            optional(self.last_commit.as_ref().map(|commit| commit.email.as_str())),
+            self.flow_branch.local,
+            self.flow_branch.remote,
+            self.pushed,
+            self.merged
+        )
+
+    }
+
+    fn text (&self) -> String {
+        format!(
+            "{} | {} | {} | base {} | {} | {}",
+            self.flow_branch.full_name,
+            self.last_commit.as_ref().map(|commit| commit.date.as_str()).unwrap_or("-"),
+            self.last_commit.as_ref().map(|commit| commit.author.as_str()).unwrap_or("-"),
+            self.base.as_deref().unwrap_or("-"),
+            if self.pushed { "pushed" } else { "not pushed" },
+            if self.merged { "merged" } else { "unmerged" }
+        )
+    }
+
+}
+
+///
+/// `git lab <type> list`: the branches of a type.
+///
+pub enum Listing {}
+
+impl Listing {
+
+    pub fn run (type_name: &str, options: &ListOptions) -> Result<(), String> {
+
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs() as i64)
+            .unwrap_or_default();
+
+        let user_email = GitV2::user_email();
+
+        let entries: Vec<Entry> = FlowBranch::all()?
+            .into_iter()
+            .filter(|flow_branch| flow_branch.branch.type_name() == type_name)
+            .map(|flow_branch| Entry {
+                base: flow_branch.branch.base().ok(),
+                last_commit: GitV2::last_commit(&flow_branch.reference()).ok(),
+                pushed: flow_branch.is_pushed(),
+                merged: flow_branch.is_merged(),
+                flow_branch,
+            })
+            .filter(|entry| !options.merged || entry.merged)
+            .filter(|entry| match (options.stale, &entry.last_commit) {
+                (Some(days), Some(commit)) => now - commit.timestamp >= days as i64 * SECONDS_PER_DAY,
+                (Some(_), None) => false,
+                (None, _) => true
+            })
+            .filter(|entry| !options.mine || entry.last_commit.as_ref().map(|commit| &commit.email) == user_email.as_ref())
+            .collect();
+
+        if options.json {
+            println!("[{}]", entries.iter().map(|entry| entry.json()).collect::<Vec<String>>().join(","));
+            return Ok(());
+        }
+
+        if entries.is_empty() {
+            info!("No {} branches", type_name);
+        }
+
+        entries.iter().for_each(|entry| info!("{}", entry.text()));
+
+        Ok(())
+
+    }
+
+}
@@ -0,0 +1,469 @@
+use std::path::PathBuf;
+
+use regex::Regex;
+
+use crate::config::constants::VERSION_FILE_KEY;
+use crate::config::store::Store;
+use crate::command::gitv2::GitV2;
+use crate::command::runner::Runner;
+use crate::{info, success};
+
+///
+/// How the version is found inside a version file.
+///
+#[derive(Debug, PartialEq, Clone)]
+pub enum FileFormat {
+    /// `version = "..."` in `[package]`, `[project]` (or at the top)
+    Toml,
+    /// The top-level `"version"` key
+    Json,
+    /// The `<version>` child of the root element (pom.xml)
+    Xml,
+    /// The whole file is the version
+    Text,
+    /// The `version` named group (or the first group) of the pattern
+    Regex(String),
+}
+
+///
+/// A project file holding the version number, declared in
+/// the git config as `PATH[:FORMAT[:PATTERN]]`:
+///
+/// ```text
+/// git config --local --add lab.flow.version.file Cargo.toml
+/// git config --local --add lab.flow.version.file package.json
+/// git config --local --add lab.flow.version.file pom.xml
+/// git config --local --add lab.flow.version.file 'chart.yaml:regex:^version: (?P<version>.+)$'
+/// ```
+///
+/// When the format is omitted, it comes from the file extension.
+///
+#[derive(Debug, PartialEq, Clone)]
+pub struct VersionFile {
+    pub path: String,
+    pub format: FileFormat,
+}
+
+impl VersionFile {
+
+    ///
+    /// Parses a version file declaration.
+    ///
+    pub fn parse (declaration: &str) -> Result<VersionFile, String> {
+
+        let mut parts = declaration.trim().splitn(3, ':');
+
+        let path = match parts.next() {
+            Some(path) if !path.is_empty() => path.to_string(),
+            _ => return Err(format!("Invalid version file '{}'", declaration))
+        };
+
+        let format = match (parts.next(), parts.next()) {
+            (None, _) => {
+                let lowercase_path = path.to_lowercase();
+                if lowercase_path.ends_with(".toml") {
+                    FileFormat::Toml
+                } else if lowercase_path.ends_with(".json") {
+                    FileFormat::Json
+                } else if lowercase_path.ends_with(".xml") {
+                    FileFormat::Xml
+                } else {
+                    FileFormat::Text
+                }
+            },
+            (Some("toml"), None) => FileFormat::Toml,
+            (Some("json"), None) => FileFormat::Json,
+            (Some("xml"), None) => FileFormat::Xml,
+            (Some("text"), None) => FileFormat::Text,
+            (Some("regex"), Some(pattern)) => FileFormat::Regex(pattern.to_string()),
+            (Some(format), _) => return Err(format!("Unknown version file format '{}' for {}", format, path))
+        };
+
+        Ok(VersionFile { path, format })
+
+    }
+
+    ///
+    /// Every version file declared in the store.
+    ///
+    pub fn all () -> Result<Vec<VersionFile>, String> {
+        match Store::get_all(VERSION_FILE_KEY) {
+            Ok(declarations) => declarations.iter().map(|declaration| VersionFile::parse(declaration)).collect(),
+            Err(_) => Ok(Vec::new())
+        }
+    }
+
+    ///
+    /// Writes the given version into the file. Tells if the
+    /// file changed (it may have the version already).
+    ///
+    pub fn update (&self, version: &str) -> Result<bool, String> {
+
+        let path: PathBuf = PathBuf::from(GitV2::root_dir()?).join(&self.path);
+
+        let content = match std::fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) => return Err(format!("Couldn't read {}: {}", self.path, e))
+        };
+
+        let updated = match &self.format {
+            FileFormat::Toml => update_toml(&content, version),
+            FileFormat::Json => update_json(&content, version),
+            FileFormat::Xml => update_xml(&content, version),
+            FileFormat::Text => Some(update_text(&content, version)),
+            FileFormat::Regex(pattern) => update_regex(&content, pattern, version)?,
+        };
+
+        let updated = match updated {
+            Some(updated) => updated,
+            None => return Err(format!("No version found in {}", self.path))
+        };
+
+        if updated == content {
+            return Ok(false);
+        }
+
+        match std::fs::write(&path, updated) {
+            Ok(_) => Ok(true),
+            Err(e) => Err(format!("Couldn't write {}: {}", self.path, e))
+        }
+
+    }
+
+    ///
+    /// Writes the version into every declared file and commits
+    /// them as "Bump version to X". Nothing is committed when
+    /// no file changed.
+    ///
+    pub fn bump_all (version: &str) -> Result<(), String> {
+
+        let mut changed_paths: Vec<String> = Vec::new();
+
+        for version_file in VersionFile::all()? {
+            if version_file.update(version)? {
+                success!("Version {} written to {}", version, &version_file.path);
+                changed_paths.push(version_file.path.clone());
+            } else {
+                info!("{} already has version {}", &version_file.path, version);
+            }
+        }
+
+        if changed_paths.is_empty() {
+            return Ok(());
+        }
+
+        let root_dir = GitV2::root_dir()?;
+
+        GitV2::add(
+            changed_paths
+                .iter()
+                .map(|path| Runner::quote(&PathBuf::from(&root_dir).join(path).to_string_lossy()))
+                .collect()
+        )?;
+
+        GitV2::commit(format!("Bump version to {}", version), false)?;
+
+        Ok(())
+
+    }
+
+}
+
+///
+/// Replaces the byte range of the old version with the new one.
+///
+fn replace_range (content: &str, start: usize, end: usize, version: &str) -> String {
+    format!("{}{}{}", &content[..start], version, &content[end..])
+}
+
+fn update_toml (content: &str, version: &str) -> Option<String> {
+
+    let section_regex = Regex::new(r"^\s*\[\s*([^\]]+?)\s*\]").ok()?;
+    let version_regex = Regex::new(r#"^\s*version\s*=\s*["']([^"']*)["']"#).ok()?;
+
+    let mut section: Option<String> = None;
+    let mut offset: usize = 0;
+
+    for line in content.split_inclusive('\n') {
+
+        if let Some(captures) = section_regex.captures(line) {
+            section = Some(captures[1].to_string());
+        } else if let Some(captures) = version_regex.captures(line) {
+
+            let is_version_section = match &section {
+                None => true,
+                Some(name) => matches!(name.as_str(), "package" | "project" | "tool.poetry" | "workspace.package"),
+            };
+
+            if is_version_section {
+                let version_match = captures.get(1)?;
+                return Some(replace_range(content, offset + version_match.start(), offset + version_match.end(), version));
+            }
+
+        }
+
+        offset += line.len();
+
+    }
+
+    None
+
+}
+
+fn update_json (content: &str, version: &str) -> Option<String> {
+
+    let bytes = content.as_bytes();
+    let mut depth: usize = 0;
+    let mut index: usize = 0;
+
+    // Strings are read as a whole, so braces inside them don't count
+    let read_string = |start: usize| -> Option<usize> {
+        let mut index = start + 1;
+        while index < bytes.len() {
+            match bytes[index] {
+                b'\\' => index += 2,
+                b'"' => return Some(index),
+                _ => index += 1,
+            }
+        }
+        None
+    };
+
+    while index < bytes.len() {
+
+        match bytes[index] {
+
+            b'{' | b'[' => depth += 1,
+
+            b'}' | b']' => depth = depth.saturating_sub(1),
+
+            b'"' => {
+
+                let end = read_string(index)?;
+                let key = &content[index + 1..end];
+
+                let mut after = end + 1;
+                while after < bytes.len() && bytes[after].is_ascii_whitespace() {
+                    after += 1;
+                }
+
+                let is_key = after < bytes.len() && bytes[after] == b':';
+
+                if depth == 1 && is_key && key == "version" {
+
+                    let mut value_start = after + 1;
+                    while value_start < bytes.len() && bytes[value_start].is_ascii_whitespace() {
+                        value_start += 1;
+                    }
+
+                    if value_start >= bytes.len() || bytes[value_start] != b'"' {
+                        return None;
+                    }
+
+                    let value_end = read_string(value_start)?;
+
+                    return Some(replace_range(content, value_start + 1, value_end, version));
+
+                }
+
+                index = end;
+
+            },
+
+            _ => {}
+
+        }
+
+        index += 1;
+
+    }
+
+    None
+
+}
+
+fn update_xml (content: &str, version: &str) -> Option<String> {
+
+    let tag_regex = Regex::new(r"<!--[\s\S]*?-->|<\?[\s\S]*?\?>|<!\[CDATA\[[\s\S]*?\]\]>|<(/?)([A-Za-z_][\w:.-]*)[^>]*?(/?)>").ok()?;
+
+    let mut stack: Vec<String> = Vec::new();
+
+    for captures in tag_regex.captures_iter(content) {
+
+        let name = match captures.get(2) {
+            Some(name) => name.as_str().to_string(),
+            // Comments, declarations and CDATA
+            None => continue
+        };
+
+        let is_closing = !captures[1].is_empty();
+        let is_self_closing = !captures[3].is_empty();
+
+        if is_closing {
+            stack.pop();
+            continue;
+        }
+
+        if is_self_closing {
+            continue;
+        }
+
+        // The version element right under the root one
+        if name == "version" && stack.len() == 1 {
+            let value_start = captures.get(0)?.end();
+            let value_end = value_start + content[value_start..].find("</version>")?;
+            return Some(replace_range(content, value_start, value_end, version));
+        }
+
+        stack.push(name);
+
+    }
+
+    None
+
+}
+
+fn update_text (content: &str, version: &str) -> String {
+    if content.ends_with('\n') {
+        format!("{}\n", version)
+    } else {
+        version.to_string()
+    }
+}
+
+fn update_regex (content: &str, pattern: &str, version: &str) -> Result<Option<String>, String> {
+
+    let regex = match Regex::new(&format!("(?m){}", pattern)) {
+        Ok(regex) => regex,
+        Err(e) => return Err(format!("Invalid version pattern '{}': {}", pattern, e))
+    };
+
+    Ok(
+        regex
+            .captures(content)
+            .and_then(|captures| captures.name("version").or_else(|| captures.get(1)))
+            .map(|version_match| replace_range(content, version_match.start(), version_match.end(), version))
+    )
+
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{FileFormat, VersionFile, update_json, update_regex, update_text, update_toml, update_xml};
+
+    #[test]
+    fn format_comes_from_the_declaration_or_the_extension () {
+
+        let format = |declaration: &str| VersionFile::parse(declaration).map(|version_file| version_file.format);
+
+        assert_eq!(format("Cargo.toml"), Ok(FileFormat::Toml));
+        assert_eq!(format("web/package.JSON"), Ok(FileFormat::Json));
+        assert_eq!(format("pom.xml"), Ok(FileFormat::Xml));
+        assert_eq!(format("VERSION"), Ok(FileFormat::Text));
+        assert_eq!(format("version.txt:toml"), Ok(FileFormat::Toml));
+        assert_eq!(format("chart.yaml:regex:^version: (?P<version>.+)$"), Ok(FileFormat::Regex(String::from("^version: (?P<version>.+)$"))));
+
+        assert!(format("").is_err());
+        assert!(format("chart.yaml:yaml").is_err());
+        assert!(format("chart.yaml:regex").is_err());
+
+    }
+
+    #[test]
+    fn toml_changes_the_package_version_only () {
+
+        let cargo = "[package]\nname = \"app\"\nversion = \"0.1.0\"\n\n[dependencies]\nserde = { version = \"1.0\" }\n\n[dependencies.regex]\nversion = \"1.5\"\n";
+
+        assert_eq!(
+            update_toml(cargo, "0.2.0").unwrap(),
+            "[package]\nname = \"app\"\nversion = \"0.2.0\"\n\n[dependencies]\nserde = { version = \"1.0\" }\n\n[dependencies.regex]\nversion = \"1.5\"\n"
+        );
+
+        // The dependencies may come first
+        let cargo = "[dependencies.regex]\nversion = \"1.5\"\n\n[package]\nversion = '0.1.0'\n";
+
+        assert_eq!(update_toml(cargo, "0.2.0").unwrap(), "[dependencies.regex]\nversion = \"1.5\"\n\n[package]\nversion = '0.2.0'\n");
+
+        let pyproject = "[build-system]\nrequires = [\"poetry\"]\n\n[tool.poetry]\nversion = \"0.1.0\"\n";
+
+        assert_eq!(update_toml(pyproject, "0.2.0").unwrap(), "[build-system]\nrequires = [\"poetry\"]\n\n[tool.poetry]\nversion = \"0.2.0\"\n");
+
+    }
+
+    #[test]
+    fn toml_without_a_package_version_has_none () {
+        assert_eq!(update_toml("[package]\nversion.workspace = true\n\n[dependencies.regex]\nversion = \"1.5\"\n", "0.2.0"), None);
+    }
+
+    #[test]
+    fn json_changes_the_top_level_version_only () {
+
+        let package = r#"{
+  "name": "app",
+  "description": "Braces { and \"version\": \"9\" } in a string",
+  "dependencies": { "lib": { "version": "1.0.0" } },
+  "files": [{ "version": "2.0.0" }],
+  "version": "0.1.0"
+}"#;
+
+        assert_eq!(update_json(package, "0.2.0").unwrap(), package.replace("\"0.1.0\"", "\"0.2.0\""));
+
+    }
+
+    #[test]
+    fn json_without_a_top_level_version_has_none () {
+        assert_eq!(update_json(r#"{"dependencies": {"version": "1.0.0"}}"#, "0.2.0"), None);
+        assert_eq!(update_json(r#"{"version": 1}"#, "0.2.0"), None);
+    }
+
+    #[test]
+    fn xml_changes_the_project_version_only () {
+
+        let pom = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!-- <version>0.0.1</version> -->
+<project xmlns="http://maven.apache.org/POM/4.0.0">
+  <parent>
+    <groupId>org.acme</groupId>
+    <version>3.0.0</version>
+  </parent>
+  <relativePath/>
+  <version>0.1.0</version>
+  <dependencies>
+    <dependency>
+      <version>1.0.0</version>
+    </dependency>
+  </dependencies>
+</project>
+"#;
+
+        assert_eq!(update_xml(pom, "0.2.0").unwrap(), pom.replace("<version>0.1.0</version>", "<version>0.2.0</version>"));
+
+    }
+
+    #[test]
+    fn xml_without_a_project_version_has_none () {
+        assert_eq!(update_xml("<project><parent><version>3.0.0</version></parent></project>", "0.2.0"), None);
+    }
+
+    #[test]
+    fn text_keeps_the_line_ending () {
+        assert_eq!(update_text("0.1.0\n", "0.2.0"), "0.2.0\n");
+        assert_eq!(update_text("0.1.0", "0.2.0"), "0.2.0");
+    }
+
+    #[test]
+    fn regex_changes_the_version_group_or_the_first_one () {
+
+        let chart = "apiVersion: v2\nversion: 0.1.0\nappVersion: 0.1.0\n";
+
+        assert_eq!(update_regex(chart, "^version: (?P<version>.+)$", "0.2.0"), Ok(Some(String::from("apiVersion: v2\nversion: 0.2.0\nappVersion: 0.1.0\n"))));
+        assert_eq!(update_regex(chart, "^(app)Version: (?P<version>.+)$", "0.2.0"), Ok(Some(String::from("apiVersion: v2\nversion: 0.1.0\nappVersion: 0.2.0\n"))));
+        assert_eq!(update_regex(chart, "^appVersion: (.+)$", "0.2.0"), Ok(Some(String::from("apiVersion: v2\nversion: 0.1.0\nappVersion: 0.2.0\n"))));
+        assert_eq!(update_regex(chart, "^release: (.+)$", "0.2.0"), Ok(None));
+
+        assert!(update_regex(chart, "^version: (.+$", "0.2.0").is_err());
+
+    }
+
+}
@@ -0,0 +1,337 @@
+use regex::Regex;
+
+use crate::config::constants::{
+    POLICY_KEY_PREFIX,
+    ISSUE_PATTERN_KEY,
+    DEFAULT_ISSUE_PATTERN,
+};
+use crate::config::store::Store;
+
+///
+/// How the branches of a type must be named, read from the
+/// git config of the repository:
+///
+/// ```text
+/// git config --local lab.flow.policy.feature.issue true
+/// git config --local lab.flow.policy.feature.regex '^[A-Z]+-[0-9]+-[a-z0-9-]+$'
+/// git config --local lab.flow.policy.feature.maxlength 50
+/// git config --local lab.flow.policy.feature.chars 'A-Za-z0-9-'
+/// git config --local lab.flow.policy.feature.lowercase true
+/// ```
+///
+/// `issue` is `true` (the key pattern of `lab.flow.policy.issue`,
+/// `[A-Z][A-Z0-9]+-[0-9]+` by default) or a pattern of its own.
+/// Every rule is optional; the name must always be a valid
+/// branch name.
+///
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct NamingPolicy {
+    pub type_name: String,
+    pub regex: Option<String>,
+    pub issue: Option<String>,
+    pub max_length: Option<usize>,
+    pub chars: Option<String>,
+    pub lowercase: bool,
+}
+
+fn key (type_name: &str, rule: &str) -> String {
+    format!("{}.{}.{}", POLICY_KEY_PREFIX, type_name, rule)
+}
+
+fn compile (pattern: &str) -> Result<Regex, String> {
+    match Regex::new(pattern) {
+        Ok(regex) => Ok(regex),
+        Err(e) => Err(format!("Invalid pattern '{}' in the naming policy: {}", pattern, e))
+    }
+}
+
+///
+/// Checks the name can be used in a branch: letters, digits,
+/// dots, dashes and underscores, with no `..` nor a trailing
+/// dot or `.lock`. Slashes belong to the prefix only:
+/// `Branch::from` reads the name back from the last segment.
+///
+fn is_given_name_valid (name: &str) -> bool {
+
+    let valid_name_regex: Regex = match Regex::new(r"^[A-Za-z0-9][A-Za-z0-9._-]*$") {
+        Ok(regex) => regex,
+        Err(_) => return false
+    };
+
+    valid_name_regex.is_match(name)
+        && !name.contains("..")
+        && !name.ends_with('.')
+        && !name.ends_with(".lock")
+
+}
+
+///
+/// The letter or digit in ASCII, accents dropped (`ã` is `a`).
+///
+fn ascii_letter (current: char) -> Option<char> {
+    match current {
+        'á' | 'à' | 'â' | 'ã' | 'ä' | 'å' => Some('a'),
+        'ç' => Some('c'),
+        'é' | 'è' | 'ê' | 'ë' => Some('e'),
+        'í' | 'ì' | 'î' | 'ï' => Some('i'),
+        'ñ' => Some('n'),
+        'ó' | 'ò' | 'ô' | 'õ' | 'ö' => Some('o'),
+        'ú' | 'ù' | 'û' | 'ü' => Some('u'),
+        'ý' | 'ÿ' => Some('y'),
+        _ if current.is_ascii_alphanumeric() => Some(current),
+        _ => None
+    }
+}
+
+impl NamingPolicy {
+
+    ///
+    /// The issue key pattern shared by every type.
+    ///
+    pub fn issue_pattern () -> String {
+        match Store::get(ISSUE_PATTERN_KEY) {
+            Ok(pattern) if !pattern.trim().is_empty() => String::from(pattern.trim()),
+            _ => String::from(DEFAULT_ISSUE_PATTERN)
+        }
+    }
+
+    ///
+    /// Finds the issue key in the given text (a branch name).
+    ///
+    pub fn issue_key (text: &str) -> Option<String> {
+        match Regex::new(&NamingPolicy::issue_pattern()) {
+            Ok(regex) => regex.find(text).map(|found| found.as_str().to_string()),
+            Err(_) => None
+        }
+    }
+
+    ///
+    /// Names a branch after an issue: its key, kept whole, followed
+    /// by its title, slugified (`ABC-123-add-the-login-form`), keeping
+    /// to the characters and length of the policy. A name that still
+    /// breaks the policy (a key longer than the maximum length) is
+    /// an error telling the rules it breaks.
+    ///
+    pub fn name_for_issue (&self, issue_key: &str, title: &str) -> Result<String, String> {
+
+        let allowed_char_regex = self.chars.as_ref().and_then(|chars| Regex::new(&format!("^[{}]$", chars)).ok());
+
+        let is_allowed = |current: char| match &allowed_char_regex {
+            Some(regex) => regex.is_match(&current.to_string()),
+            None => true
+        };
+
+        let separator = match is_allowed('-') {
+            true => "-",
+            false => ""
+        };
+
+        let words: Vec<String> = title
+            .to_lowercase()
+            .split(|current: char| !current.is_alphanumeric())
+            .map(|word| word.chars().filter_map(ascii_letter).filter(|current| is_allowed(*current)).collect::<String>())
+            .filter(|word| !word.is_empty())
+            .collect();
+
+        // The key isn't held to the characters, as in `check`
+        let mut name = issue_key.to_string();
+
+        // Whole words only, as long as they fit
+        for word in words.iter() {
+
+            let next = match name.is_empty() {
+                true => word.clone(),
+                false => format!("{}{}{}", name, separator, word)
+            };
+
+            if self.max_length.is_some_and(|max_length| next.chars().count() > max_length) {
+                break;
+            }
+
+            name = next;
+
+        }
+
+        self.check(&name)?;
+
+        Ok(name)
+
+    }
+
+    ///
+    /// Loads the naming policy of the given branch type.
+    ///
+    pub fn load (type_name: &str) -> NamingPolicy {
+
+        let rule = |name: &str| match Store::get(&key(type_name, name)) {
+            Ok(value) if !value.trim().is_empty() => Some(String::from(value.trim())),
+            _ => None
+        };
+
+        let issue = match rule("issue") {
+            Some(value) if value == "true" => Some(NamingPolicy::issue_pattern()),
+            Some(value) if value == "false" => None,
+            other => other
+        };
+
+        NamingPolicy {
+            type_name: type_name.to_string(),
+            regex: rule("regex"),
+            issue,
+            max_length: rule("maxlength").and_then(|value| value.parse::<usize>().ok()),
+            chars: rule("chars"),
+            lowercase: rule("lowercase").as_deref() == Some("true"),
+        }
+
+    }
+
+    ///
+    /// Checks the branch name (without its prefix) against the
+    /// policy, telling every rule it breaks.
+    ///
+    pub fn check (&self, name: &str) -> Result<(), String> {
+
+        let mut broken_rules: Vec<String> = Vec::new();
+
+        if !is_given_name_valid(name) {
+            broken_rules.push(String::from("use only letters, digits, '.', '-' and '_' (no '/', no '..', nor a trailing '.' or '.lock')"));
+        }
+
+        let issue_key = match &self.issue {
+            Some(pattern) => {
+                let issue_key = compile(pattern)?.find(name).map(|found| found.as_str().to_string());
+                if issue_key.is_none() {
+                    broken_rules.push(format!("contain an issue key matching {}", pattern));
+                }
+                issue_key
+            },
+            None => NamingPolicy::issue_key(name)
+        };
+
+        // Issue keys are usually uppercase, so they don't count for the characters nor the case
+        let rest = match &issue_key {
+            Some(issue_key) => name.replacen(issue_key.as_str(), "", 1),
+            None => name.to_string()
+        };
+
+        if let Some(max_length) = self.max_length {
+            if name.chars().count() > max_length {
+                broken_rules.push(format!("have at most {} characters (it has {})", max_length, name.chars().count()));
+            }
+        }
+
+        if let Some(chars) = &self.chars {
+            if !compile(&format!("^[{}]*$", chars))?.is_match(&rest) {
+                broken_rules.push(format!("use only the characters [{}] (besides the issue key)", chars));
+            }
+        }
+
+        if self.lowercase && rest != rest.to_lowercase() {
+            broken_rules.push(String::from("be lowercase (besides the issue key)"));
+        }
+
+        if let Some(regex) = &self.regex {
+            if !compile(regex)?.is_match(name) {
+                broken_rules.push(format!("match {}", regex));
+            }
+        }
+
+        if broken_rules.is_empty() {
+            return Ok(());
+        }
+
+        Err(format!(
+            "The {} name '{}' doesn't follow the naming policy. It must:\n  - {}",
+            self.type_name,
+            name,
+            broken_rules.join("\n  - ")
+        ))
+
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{is_given_name_valid, NamingPolicy};
+
+    fn policy (max_length: Option<usize>, chars: Option<&str>) -> NamingPolicy {
+        NamingPolicy { type_name: String::from("feature"), max_length, chars: chars.map(String::from), ..NamingPolicy::default() }
+    }
+
+    fn named (policy: NamingPolicy, title: &str) -> Result<String, String> {
+        policy.name_for_issue("ABC-123", title)
+    }
+
+    #[test]
+    fn issue_name_is_the_key_and_the_slug_of_the_title () {
+        assert_eq!(named(policy(None, None), "Add the login form!"), Ok(String::from("ABC-123-add-the-login-form")));
+    }
+
+    #[test]
+    fn issue_name_drops_accents_and_other_letters () {
+        assert_eq!(policy(None, None).name_for_issue("ABC-7", "Corrigir a sessão: ação (ÚNICA) ✓ 日本"), Ok(String::from("ABC-7-corrigir-a-sessao-acao-unica")));
+    }
+
+    #[test]
+    fn issue_name_keeps_whole_words_within_the_max_length () {
+
+        assert_eq!(named(policy(Some(20), None), "Add the login form"), Ok(String::from("ABC-123-add-the")));
+        assert_eq!(named(policy(Some(15), None), "Add the login form"), Ok(String::from("ABC-123-add-the")));
+        assert_eq!(named(policy(Some(14), None), "Add the login form"), Ok(String::from("ABC-123-add")));
+        assert_eq!(named(policy(Some(7), None), "Add the login form"), Ok(String::from("ABC-123")));
+
+    }
+
+    #[test]
+    fn issue_name_fails_when_the_key_is_too_long () {
+
+        let error = named(policy(Some(6), None), "Add the login form").unwrap_err();
+
+        assert!(error.contains("at most 6 characters"), "{}", error);
+
+    }
+
+    #[test]
+    fn issue_name_keeps_the_key_whole_and_the_title_to_the_chars () {
+
+        // The key isn't held to the characters, so the name doesn't start with a dash
+        assert_eq!(named(policy(None, Some("a-z0-9-")), "Add Login"), Ok(String::from("ABC-123-add-login")));
+        assert_eq!(named(policy(None, Some("A-Za-z0-9")), "Add login"), Ok(String::from("ABC-123addlogin")));
+
+    }
+
+    #[test]
+    fn issue_name_follows_the_whole_policy () {
+
+        let strict = NamingPolicy {
+            type_name: String::from("feature"),
+            issue: Some(String::from("[A-Z][A-Z0-9]+-[0-9]+")),
+            max_length: Some(30),
+            chars: Some(String::from("a-z0-9-")),
+            lowercase: true,
+            regex: Some(String::from("^[A-Z]+-[0-9]+-")),
+        };
+
+        let name = named(strict.clone(), "Ação: Add the login form").unwrap();
+
+        assert_eq!(name, "ABC-123-acao-add-the-login");
+        assert_eq!(strict.check(&name), Ok(()));
+
+    }
+
+    #[test]
+    fn given_names_have_no_slashes_nor_dots_in_the_wrong_places () {
+
+        for valid in ["ABC-123-login", "1.4.0", "my_branch", "v2"] {
+            assert!(is_given_name_valid(valid), "{}", valid);
+        }
+
+        for invalid in ["", "team/login", "-login", ".login", "a..b", "login.", "login.lock", "log in", "sessão"] {
+            assert!(!is_given_name_valid(invalid), "{}", invalid);
+        }
+
+    }
+
+}
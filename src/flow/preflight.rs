@@ -0,0 +1,249 @@
+use std::fmt::Display;
+
+use crate::command::gitv2::GitV2;
+use crate::config::error::{
+    NOT_INITIATED_ERROR_CODE,
+    DIRTY_TREE_ERROR_CODE,
+    DETACHED_HEAD_ERROR_CODE,
+    OPERATION_IN_PROGRESS_ERROR_CODE,
+    BASE_BEHIND_ERROR_CODE,
+    LINT_ERROR_CODE,
+};
+use crate::flow::branch::Branch;
+use crate::flow::finish::FinishState;
+use crate::flow::init::Script;
+use crate::flow::lint::{Lint, LintRules};
+use crate::info;
+
+/// Message of the stash made by `--autostash`
+const AUTOSTASH_MESSAGE: &str = "lab-flow autostash";
+
+///
+/// Why a start or a finish can't even begin.
+///
+#[derive(Debug, PartialEq, Clone)]
+pub enum PreflightError {
+    NotInitiated,
+    /// The files with uncommitted changes
+    DirtyTree(Vec<String>),
+    DetachedHead,
+    /// The git operation waiting to be concluded (merge, rebase, ...)
+    OperationInProgress(String),
+    /// The base branch and how many commits its remote has ahead of it
+    BaseBehind(String, u32),
+    /// The branch and its commits that don't follow Conventional Commits
+    BadCommits(String, Vec<String>),
+}
+
+impl PreflightError {
+
+    ///
+    /// The exit code of the process.
+    ///
+    pub fn code (&self) -> i32 {
+        match self {
+            PreflightError::NotInitiated => NOT_INITIATED_ERROR_CODE,
+            PreflightError::DirtyTree(_) => DIRTY_TREE_ERROR_CODE,
+            PreflightError::DetachedHead => DETACHED_HEAD_ERROR_CODE,
+            PreflightError::OperationInProgress(_) => OPERATION_IN_PROGRESS_ERROR_CODE,
+            PreflightError::BaseBehind(_, _) => BASE_BEHIND_ERROR_CODE,
+            PreflightError::BadCommits(_, _) => LINT_ERROR_CODE,
+        }
+    }
+
+}
+
+impl Display for PreflightError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PreflightError::NotInitiated => write!(
+                f,
+                "This is not a lab flow repository: run `git lab init` first."
+            ),
+            PreflightError::DirtyTree(files) => write!(
+                f,
+                "There are uncommitted changes in: {}. Commit or stash them, or use --autostash.",
+                files.join(", ")
+            ),
+            PreflightError::DetachedHead => write!(
+                f,
+                "HEAD is detached: check out a branch first."
+            ),
+            PreflightError::OperationInProgress(operation) => write!(
+                f,
+                "There is a {} in progress: conclude or abort it first.",
+                operation
+            ),
+            PreflightError::BaseBehind(base, count) => write!(
+                f,
+                "{} is {} commit(s) behind origin/{}: update it first (git pull).",
+                base,
+                count,
+                base
+            ),
+            PreflightError::BadCommits(branch, commits) => write!(
+                f,
+                "These commits of {} don't follow Conventional Commits:\n  - {}\nReword them (git rebase -i) first.",
+                branch,
+                commits.join("\n  - ")
+            ),
+        }
+    }
+}
+
+///
+/// The checks run before a start or a finish, so they don't
+/// stop halfway because of the state of the repository.
+///
+pub enum Preflight {}
+
+impl Preflight {
+
+    pub fn initiated () -> Result<(), PreflightError> {
+        match Script::is_initiated() {
+            true => Ok(()),
+            false => Err(PreflightError::NotInitiated)
+        }
+    }
+
+    pub fn attached_head () -> Result<(), PreflightError> {
+        match GitV2::current_branch() {
+            Some(_) => Ok(()),
+            None => Err(PreflightError::DetachedHead)
+        }
+    }
+
+    pub fn no_operation_in_progress () -> Result<(), PreflightError> {
+
+        let operation = if GitV2::is_rebasing() {
+            "rebase"
+        } else if GitV2::is_merging() {
+            "merge"
+        } else if GitV2::is_picking() {
+            "cherry-pick (or revert)"
+        } else {
+            return Ok(());
+        };
+
+        Err(PreflightError::OperationInProgress(operation.to_string()))
+
+    }
+
+    ///
+    /// Checks the working tree has no uncommitted changes. With
+    /// `autostash`, they are stashed instead: gives the stash
+    /// (its commit) if they were.
+    ///
+    pub fn clean_tree (autostash: bool) -> Result<Option<String>, PreflightError> {
+
+        let changed_files = GitV2::changed_files().unwrap_or_default();
+
+        if changed_files.is_empty() {
+            return Ok(None);
+        }
+
+        if !autostash {
+            return Err(PreflightError::DirtyTree(changed_files));
+        }
+
+        match GitV2::stash(AUTOSTASH_MESSAGE).and_then(|_| GitV2::rev_parse("refs/stash")) {
+            Ok(stash) => {
+                info!("Uncommitted changes stashed ({}, {})", AUTOSTASH_MESSAGE, &stash);
+                Ok(Some(stash))
+            },
+            Err(_) => Err(PreflightError::DirtyTree(changed_files))
+        }
+
+    }
+
+    ///
+    /// Checks the base branch isn't behind its remote-tracking
+    /// branch (as of the last fetch).
+    ///
+    pub fn base_up_to_date (base: &str) -> Result<(), PreflightError> {
+
+        let remote_base = format!("origin/{}", base);
+
+        // Not on the remote (or no remote at all): nothing to be behind of
+        if GitV2::rev_parse(&remote_base).is_err() || GitV2::rev_parse(base).is_err() {
+            return Ok(());
+        }
+
+        match GitV2::count_commits(base, &remote_base) {
+            Ok(0) | Err(_) => Ok(()),
+            Ok(count) => Err(PreflightError::BaseBehind(base.to_string(), count))
+        }
+
+    }
+
+    ///
+    /// Checks the commits of feature and bugfix branches follow
+    /// Conventional Commits, when `lab.flow.lint.enabled` is set.
+    ///
+    pub fn lint (branch: &Branch) -> Result<(), PreflightError> {
+
+        if !LintRules::is_enabled() || !LintRules::applies_to(branch) {
+            return Ok(());
+        }
+
+        // A branch that can't be read fails later, with its own error
+        match Lint::bad_commits(&branch.full_name()) {
+            Ok(bad_commits) if !bad_commits.is_empty() => Err(PreflightError::BadCommits(branch.full_name(), Lint::report(&bad_commits))),
+            _ => Ok(())
+        }
+
+    }
+
+    ///
+    /// Brings back the changes stashed by `--autostash` (the stash
+    /// commit) once the action is over. They are kept stashed when
+    /// the action left the repository elsewhere: a finish stopped on
+    /// conflicts gives them back on `--continue` or `--abort`, and a
+    /// failure may leave another branch than `stashed_from` checked out.
+    ///
+    pub fn restore_stash (stash: &str, stashed_from: Option<&str>, succeeded: bool) -> Result<(), String> {
+
+        if FinishState::exists() {
+
+            let mut state = FinishState::load()?;
+            state.stash = Some(stash.to_string());
+            state.save()?;
+
+            info!(
+                "Your uncommitted changes are kept stashed ({}, {}): they come back with `git lab finish --continue` or `git lab finish --abort`",
+                AUTOSTASH_MESSAGE,
+                stash
+            );
+
+            return Ok(());
+
+        }
+
+        if !succeeded && GitV2::current_branch().as_deref() != stashed_from {
+            info!(
+                "Your uncommitted changes are kept stashed ({}, {}): {} isn't checked out anymore. Bring them back with `git stash pop`",
+                AUTOSTASH_MESSAGE,
+                stash,
+                stashed_from.unwrap_or("their branch")
+            );
+            return Ok(());
+        }
+
+        Preflight::pop_stash(stash)
+
+    }
+
+    ///
+    /// Brings back the changes of the stash (its commit).
+    ///
+    pub fn pop_stash (stash: &str) -> Result<(), String> {
+        match GitV2::stash_pop(stash) {
+            Ok(_) => {
+                info!("Stashed changes restored");
+                Ok(())
+            },
+            Err(e) => Err(format!("Couldn't restore the stashed changes ({}, {}), they are kept in `git stash list`: {}", AUTOSTASH_MESSAGE, stash, e))
+        }
+    }
+
+}
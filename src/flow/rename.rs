@@ -0,0 +1,187 @@
+use crate::command::gitlab::GitLab;
+use crate::command::gitv2::GitV2;
+use crate::config::constants::BRANCH_BASE_KEY_SUFFIX;
+use crate::config::store::Store;
+use crate::flow::branch::Branch;
+use crate::flow::finish::FinishState;
+use crate::flow::policy::NamingPolicy;
+use crate::flow::transaction::Step;
+use crate::flow::version::Version;
+use crate::{error, info, success, warning, working};
+
+///
+/// `git lab <type> rename OLD NEW`: renames a flow branch locally
+/// and on the remote, all or nothing.
+///
+pub enum Rename {}
+
+impl Rename {
+
+    ///
+    /// Checks the branch can be renamed to the new name, before
+    /// touching anything. Tells if it is on the remote.
+    ///
+    fn check (branch: &Branch, renamed: &Branch, force: bool) -> Result<bool, String> {
+
+        let (from, to) = (branch.full_name(), renamed.full_name());
+
+        if GitV2::rev_parse(&format!("refs/heads/{}", from)).is_err() {
+            return Err(format!("There is no local branch {} to rename.", from));
+        }
+
+        if FinishState::exists() {
+            return Err(String::from("A finish is in progress: end it (`git lab finish --continue` or `--abort`) before renaming."));
+        }
+
+        // Releases are named after their version
+        if let Branch::Release(name) = renamed {
+            if Version::parse(name).is_none() {
+                return Err(format!("The release name '{}' is not a semantic version (like 1.4.0).", name));
+            }
+        }
+
+        NamingPolicy::load(&renamed.type_name()).check(renamed.name())?;
+
+        if GitV2::rev_parse(&format!("refs/heads/{}", to)).is_ok() {
+            return Err(format!("The branch {} already exists.", to));
+        }
+
+        if !GitV2::is_remote() {
+            return Ok(false);
+        }
+
+        if GitV2::remote_commit(&to).is_some() {
+            return Err(format!("The branch {} already exists on the remote.", to));
+        }
+
+        let remote_commit = match GitV2::remote_commit(&from) {
+            Some(remote_commit) => remote_commit,
+            None => return Ok(false)
+        };
+
+        // The remote branch is deleted: nothing of it may be lost
+        if !GitV2::is_ancestor(&remote_commit, &from) {
+            return Err(format!("The remote {} has commits the local one hasn't: pull them before renaming.", from));
+        }
+
+        if force {
+            return Ok(true);
+        }
+
+        match GitLab::open_merge_requests(&from).map(|merge_requests| merge_requests.into_iter().next()) {
+            Ok(Some(merge_request)) => Err(format!(
+                "The merge request '{}' of {} is still open: renaming leaves it without its source branch. Use --force to rename anyway.",
+                merge_request.title,
+                from
+            )),
+            Ok(None) => Ok(true),
+            // An open one may be there: not renaming it unless told to
+            Err(e) => Err(format!(
+                "Couldn't check for merge requests of {} ({}): use --force to rename anyway.",
+                from,
+                e.trim()
+            ))
+        }
+
+    }
+
+    ///
+    /// The branches started from the old name are now started
+    /// from the new one.
+    ///
+    fn move_bases (from: &str, to: &str) {
+
+        let pattern = format!(r"^branch\..*\.{}$", BRANCH_BASE_KEY_SUFFIX);
+
+        for (key, base) in Store::get_regexp(&pattern).into_iter().filter(|(_, base)| base == from) {
+            match Store::set(&key, to) {
+                Ok(_) => { info!("{} is now the base of {}", to, key.trim_start_matches("branch.").trim_end_matches(&format!(".{}", BRANCH_BASE_KEY_SUFFIX))); },
+                Err(e) => { warning!("Couldn't change the base recorded in {} from {} to {}: {}", key, base, to, e.trim()); }
+            }
+        }
+
+    }
+
+    ///
+    /// Renames the local branch (its recorded base and merge request
+    /// go along, in its config), pushes the new name and deletes
+    /// the old one from the remote. Any failure undoes it all.
+    ///
+    pub fn run (branch: &Branch, renamed: &Branch, force: bool) -> Result<(), String> {
+
+        let (from, to) = (branch.full_name(), renamed.full_name());
+
+        working!("Renaming {} to {}", &from, &to);
+
+        let on_remote = Rename::check(branch, renamed, force)?;
+
+        let mut steps: Vec<Step> = Vec::new();
+
+        let result = Rename::steps(&from, &to, on_remote, &mut steps);
+
+        if let Err(e) = result {
+
+            error!("{}", e.trim());
+
+            let mut undone_all = true;
+
+            for step in steps.iter().rev() {
+                match step.undo() {
+                    Ok(undone) => { info!("Undone: {}", undone); },
+                    Err(e) => {
+                        error!("Not undone: {}", e);
+                        undone_all = false;
+                    }
+                }
+            }
+
+            // The upstream was moved to the new name by the push
+            if on_remote && GitV2::rev_parse(&format!("refs/heads/{}", from)).is_ok() {
+                if let Err(e) = GitV2::set_upstream(&from) {
+                    warning!("Couldn't make {} track origin/{} again: {}", &from, &from, e.trim());
+                }
+            }
+
+            return match undone_all {
+                true => Err(format!("Couldn't rename {} to {}: everything was undone.", from, to)),
+                false => Err(format!("Couldn't rename {} to {}, nor undo it all: see above what is left.", from, to))
+            };
+
+        }
+
+        Rename::move_bases(&from, &to);
+
+        success!("Branch {} renamed to {}", from, to);
+
+        Ok(())
+
+    }
+
+    fn steps (from: &str, to: &str, on_remote: bool, steps: &mut Vec<Step>) -> Result<(), String> {
+
+        GitV2::rename_branch(from, to)?;
+
+        steps.push(Step::Rename { from: from.to_string(), to: to.to_string() });
+
+        success!("Local branch {} renamed to {}", from, to);
+
+        if !on_remote {
+            return Ok(());
+        }
+
+        GitV2::push(to, true)?;
+
+        steps.push(Step::Push { branch: to.to_string(), before: None });
+
+        success!("{} pushed, and tracked", to);
+
+        // The last step: nothing is left to undo after it
+        GitV2::remove_remote_branch(from)?;
+
+        success!("{} removed from the remote", from);
+
+        Ok(())
+
+    }
+
+}
@@ -0,0 +1,264 @@
+use crate::command::gitv2::GitV2;
+use crate::config::constants::{
+    MAIN_BRANCH_NAME_KEY,
+    DEVELOP_BRANCH_NAME_KEY,
+};
+use crate::config::store::Store;
+use crate::flow::branch::Branch;
+use crate::flow::version::Version;
+use crate::{info, success, warning};
+
+fn setting (key: &str) -> Option<String> {
+    match Store::get(key) {
+        Ok(value) if !value.trim().is_empty() => Some(String::from(value.trim())),
+        _ => None
+    }
+}
+
+///
+/// A flow branch (feature, bugfix, hotfix, release, support or
+/// custom) found locally, on the remote or both.
+///
+#[derive(Debug, PartialEq, Clone)]
+pub struct FlowBranch {
+    pub branch: Branch,
+    pub full_name: String,
+    pub local: bool,
+    /// On the remote, as of the last fetch
+    pub remote: bool,
+}
+
+impl FlowBranch {
+
+    ///
+    /// Every flow branch, local or remote, by name.
+    ///
+    pub fn all () -> Result<Vec<FlowBranch>, String> {
+
+        let local_branches = GitV2::local_branches()?;
+        let remote_branches = GitV2::remote_branches().unwrap_or_default();
+
+        let mut full_names: Vec<&String> = local_branches.iter().chain(remote_branches.iter()).collect();
+
+        full_names.sort();
+        full_names.dedup();
+
+        Ok(
+            full_names
+                .into_iter()
+                .filter_map(|full_name| match Branch::from(full_name) {
+                    Some(Branch::Main(_) | Branch::Develop(_)) | None => None,
+                    Some(branch) => Some(FlowBranch {
+                        branch,
+                        full_name: full_name.clone(),
+                        local: local_branches.contains(full_name),
+                        remote: remote_branches.contains(full_name),
+                    })
+                })
+                .collect()
+        )
+
+    }
+
+    ///
+    /// The reference of the branch: the local one or, when
+    /// there is none, the remote-tracking one.
+    ///
+    pub fn reference (&self) -> String {
+        match self.local {
+            true => self.full_name.clone(),
+            false => format!("origin/{}", self.full_name)
+        }
+    }
+
+    ///
+    /// The reference of the base: the local one or, when there
+    /// is none, the remote-tracking one.
+    ///
+    fn base_reference (&self) -> Option<String> {
+
+        let base = self.branch.base().ok()?;
+
+        match GitV2::rev_parse(&base) {
+            Ok(_) => Some(base),
+            Err(_) => Some(format!("origin/{}", base))
+        }
+
+    }
+
+    ///
+    /// How many commits the branch has that its base hasn't
+    /// (ahead) and the other way around (behind).
+    ///
+    pub fn ahead_behind (&self) -> Option<(String, u32, u32)> {
+
+        let base = self.branch.base().ok()?;
+        let base_reference = self.base_reference()?;
+
+        let ahead = GitV2::count_commits(&base_reference, &self.reference()).ok()?;
+        let behind = GitV2::count_commits(&self.reference(), &base_reference).ok()?;
+
+        Some((base, ahead, behind))
+
+    }
+
+    ///
+    /// Where the branch finishes. Without the local branch, its
+    /// history can't tell: the targets of its type are taken.
+    ///
+    pub fn targets (&self) -> Vec<String> {
+
+        if self.local || matches!(self.branch, Branch::Custom(_, _)) {
+            return match self.branch.source() {
+                Ok(targets) => targets.iter().map(|target| target.full_name()).collect(),
+                Err(_) => Vec::new()
+            };
+        }
+
+        let base = match self.branch.base() {
+            Ok(base) => base,
+            Err(_) => return Vec::new()
+        };
+
+        match (&self.branch, setting(MAIN_BRANCH_NAME_KEY)) {
+            (Branch::Release(_) | Branch::Hotfix(_), Some(main_name)) if main_name != base => vec![main_name, base],
+            _ => vec![base]
+        }
+
+    }
+
+    ///
+    /// Tells if the branch is on the remote with no local commits
+    /// left to push.
+    ///
+    pub fn is_pushed (&self) -> bool {
+        self.remote && (!self.local || GitV2::count_commits(&format!("origin/{}", self.full_name), &self.full_name) == Ok(0))
+    }
+
+    ///
+    /// Tells if the branch has work of its own (by its reflog or,
+    /// once that expired, by its history) and that work is in all
+    /// of its targets.
+    ///
+    pub fn is_merged (&self) -> bool {
+
+        let tip = match GitV2::rev_parse(&self.reference()) {
+            Ok(tip) => tip,
+            Err(_) => return false
+        };
+
+        // A branch just started is contained in its targets too
+        if !self.branch.has_work(&self.reference()) {
+            return false;
+        }
+
+        let targets = self.targets();
+
+        !targets.is_empty() && targets.iter().all(|target| {
+            let target = match GitV2::rev_parse(target) {
+                Ok(_) => target.clone(),
+                Err(_) => format!("origin/{}", target)
+            };
+            GitV2::is_ancestor(&tip, &target) || GitV2::is_content_merged(&tip, &target)
+        })
+
+    }
+
+    ///
+    /// Where the branch is: `local`, `remote` or `local, remote`.
+    ///
+    pub fn location (&self) -> String {
+        [(self.local, "local"), (self.remote, "remote")]
+            .iter()
+            .filter(|(found, _)| *found)
+            .map(|(_, location)| *location)
+            .collect::<Vec<&str>>()
+            .join(", ")
+    }
+
+}
+
+///
+/// `git lab status`: what's in flight, at a glance.
+///
+pub enum Status {}
+
+impl Status {
+
+    pub fn run () -> Result<(), String> {
+
+        let current = GitV2::current_branch();
+
+        match current.as_deref().map(|current| (current, Branch::from(current))) {
+            Some((current, Some(branch))) => { info!("On {} ({})", current, branch.type_name()); },
+            Some((current, None)) => { info!("On {} (not a flow branch)", current); },
+            None => { info!("HEAD is detached"); }
+        }
+
+        let flow_branches = FlowBranch::all()?;
+
+        let mut type_names: Vec<String> = flow_branches.iter().map(|flow_branch| flow_branch.branch.type_name()).collect();
+
+        type_names.sort();
+        type_names.dedup();
+
+        if flow_branches.is_empty() {
+            info!("No flow branches");
+        }
+
+        // Remote branches are as of the last fetch: status doesn't go online
+        for type_name in type_names.iter() {
+
+            info!("{} branches:", type_name);
+
+            for flow_branch in flow_branches.iter().filter(|flow_branch| &flow_branch.branch.type_name() == type_name) {
+
+                let marker = match current.as_deref() == Some(flow_branch.full_name.as_str()) {
+                    true => "*",
+                    false => " "
+                };
+
+                let counts = match flow_branch.ahead_behind() {
+                    Some((base, ahead, behind)) => format!("{} ahead, {} behind {}", ahead, behind, base),
+                    None => String::from("base not found")
+                };
+
+                info!("  {} {} ({}): {}", marker, flow_branch.full_name, flow_branch.location(), counts);
+
+            }
+
+        }
+
+        let open_releases: Vec<&str> = flow_branches
+            .iter()
+            .filter(|flow_branch| matches!(flow_branch.branch, Branch::Release(_) | Branch::Hotfix(_)))
+            .map(|flow_branch| flow_branch.full_name.as_str())
+            .collect();
+
+        match open_releases.is_empty() {
+            true => { info!("No release nor hotfix in progress"); },
+            false => { info!("Releases and hotfixes in progress: {}", open_releases.join(", ")); }
+        }
+
+        let (main_name, develop_name) = match (setting(MAIN_BRANCH_NAME_KEY), setting(DEVELOP_BRANCH_NAME_KEY)) {
+            (Some(main_name), Some(develop_name)) => (main_name, develop_name),
+            _ => return Err(String::from("Main and develop branch names not found: run `git lab init`."))
+        };
+
+        match Version::latest_tag(&main_name) {
+            Ok(Some(tag)) => { info!("Latest tag on {}: {}", &main_name, tag); },
+            Ok(None) => { info!("No version tagged on {} yet", &main_name); },
+            Err(e) => { warning!("Couldn't find the tags on {}: {}", &main_name, e.trim()); }
+        }
+
+        match GitV2::count_commits(&develop_name, &main_name) {
+            Ok(0) => { success!("{} contains everything in {}", &develop_name, &main_name); },
+            Ok(missing) => { warning!("{} misses {} commit(s) of {}: merge {} into {}", &develop_name, missing, &main_name, &main_name, &develop_name); },
+            Err(e) => { warning!("Couldn't compare {} with {}: {}", &develop_name, &main_name, e.trim()); }
+        }
+
+        Ok(())
+
+    }
+
+}
@@ -0,0 +1,72 @@
+use std::fmt::Display;
+
+use crate::config::constants::MERGE_STRATEGY_KEY_PREFIX;
+use crate::config::store::Store;
+
+///
+/// How a finished branch is merged into its targets
+/// when the merge happens locally (no merge request).
+///
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum MergeStrategy {
+    FastForward,
+    NoFastForward,
+    Squash,
+    Rebase,
+}
+
+impl MergeStrategy {
+
+    ///
+    /// Parses the strategy from its configuration text.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// assert_eq!(MergeStrategy::parse("no-ff"), Some(MergeStrategy::NoFastForward));
+    /// ```
+    ///
+    pub fn parse (text: &str) -> Option<MergeStrategy> {
+        match text.trim().to_lowercase().as_str() {
+            "ff" | "fast-forward" => Some(MergeStrategy::FastForward),
+            "no-ff" | "merge" => Some(MergeStrategy::NoFastForward),
+            "squash" => Some(MergeStrategy::Squash),
+            "rebase" => Some(MergeStrategy::Rebase),
+            _ => None
+        }
+    }
+
+    ///
+    /// The strategy configured for the given branch type
+    /// (`lab.flow.merge.feature`), if any.
+    ///
+    pub fn configured (type_name: &str) -> Option<MergeStrategy> {
+        match Store::get(&format!("{}.{}", MERGE_STRATEGY_KEY_PREFIX, type_name)) {
+            Ok(text) => MergeStrategy::parse(&text),
+            Err(_) => None
+        }
+    }
+
+    ///
+    /// The strategy used when the branch type has none:
+    /// `lab.flow.merge.default` or a merge commit (no-ff).
+    ///
+    pub fn default_strategy () -> MergeStrategy {
+        match MergeStrategy::configured("default") {
+            Some(strategy) => strategy,
+            None => MergeStrategy::NoFastForward
+        }
+    }
+
+}
+
+impl Display for MergeStrategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MergeStrategy::FastForward => write!(f, "ff"),
+            MergeStrategy::NoFastForward => write!(f, "no-ff"),
+            MergeStrategy::Squash => write!(f, "squash"),
+            MergeStrategy::Rebase => write!(f, "rebase"),
+        }
+    }
+}
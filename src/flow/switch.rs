@@ -0,0 +1,260 @@
+use crate::command::gitv2::GitV2;
+use crate::flow::status::FlowBranch;
+use crate::{info, success, working};
+
+///
+/// How well a branch name matches what was typed, best first.
+///
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+enum Match {
+    /// The full name, or the name without the prefix
+    Exact,
+    /// A whole part of the name (`123` in `feature/ABC-123-login`)
+    Part,
+    /// Anywhere in the name (`log` in `feature/ABC-123-login`)
+    Substring,
+    /// Its characters, in order (`a123lg` in `feature/ABC-123-login`)
+    Fuzzy,
+}
+
+fn is_separator (current: Option<char>) -> bool {
+    current.is_none_or(|current| !current.is_alphanumeric())
+}
+
+///
+/// How the query matches the branch, if it does (case aside).
+///
+fn matching (query: &str, flow_branch: &FlowBranch) -> Option<Match> {
+
+    let query = query.trim().to_lowercase();
+
+    // Nothing typed matches nothing (every name has the empty string)
+    if query.is_empty() {
+        return None;
+    }
+
+    let full_name = flow_branch.full_name.to_lowercase();
+
+    if full_name == query || flow_branch.branch.name().to_lowercase() == query {
+        return Some(Match::Exact);
+    }
+
+    let mut best: Option<Match> = None;
+
+    for (index, _) in full_name.match_indices(query.as_str()) {
+
+        let before = full_name[..index].chars().next_back();
+        let after = full_name[index + query.len()..].chars().next();
+
+        if is_separator(before) && is_separator(after) {
+            return Some(Match::Part);
+        }
+
+        best = Some(Match::Substring);
+
+    }
+
+    if best.is_some() {
+        return best;
+    }
+
+    let mut name_chars = full_name.chars();
+
+    match query.chars().all(|wanted| name_chars.any(|current| current == wanted)) {
+        true => Some(Match::Fuzzy),
+        false => None
+    }
+
+}
+
+///
+/// The branches matching the query best.
+///
+fn best_matches (query: &str, flow_branches: Vec<FlowBranch>) -> Vec<FlowBranch> {
+
+    let matched: Vec<(Match, FlowBranch)> = flow_branches
+        .into_iter()
+        .filter_map(|flow_branch| matching(query, &flow_branch).map(|found| (found, flow_branch)))
+        .collect();
+
+    let best = match matched.iter().map(|(found, _)| *found).min() {
+        Some(best) => best,
+        None => return Vec::new()
+    };
+
+    matched.into_iter().filter(|(found, _)| *found == best).map(|(_, flow_branch)| flow_branch).collect()
+
+}
+
+///
+/// `git lab [<type>] checkout QUERY`: checks out the flow branch
+/// whose name matches what was typed, local or remote.
+///
+pub enum Switch {}
+
+impl Switch {
+
+    ///
+    /// The branches matching the query best, among the ones of
+    /// the given type (or all of them).
+    ///
+    fn candidates (type_name: Option<&str>, query: &str) -> Result<Vec<FlowBranch>, String> {
+
+        let flow_branches: Vec<FlowBranch> = FlowBranch::all()?
+            .into_iter()
+            .filter(|flow_branch| type_name.is_none_or(|type_name| flow_branch.branch.type_name() == type_name))
+            .collect();
+
+        Ok(best_matches(query, flow_branches))
+
+    }
+
+    ///
+    /// Asks which of the branches is meant.
+    ///
+    fn choose (query: &str, candidates: Vec<FlowBranch>) -> Result<FlowBranch, String> {
+
+        info!("More than one branch matches '{}':", query);
+
+        for (index, candidate) in candidates.iter().enumerate() {
+            info!("  {}. {} ({})", index + 1, candidate.full_name, candidate.location());
+        }
+
+        info!("Which one? [1-{}]", candidates.len());
+
+        let mut answer = String::new();
+
+        if std::io::stdin().read_line(&mut answer).is_err() {
+            return Err(String::from("Nothing was checked out."));
+        }
+
+        match answer.trim().parse::<usize>().ok().and_then(|choice| candidates.into_iter().nth(choice.wrapping_sub(1))) {
+            Some(chosen) => Ok(chosen),
+            None => Err(String::from("Nothing was checked out: type more of the name to tell the branches apart."))
+        }
+
+    }
+
+    pub fn run (type_name: Option<&str>, query: &str) -> Result<(), String> {
+
+        if query.trim().is_empty() {
+            return Err(String::from("Tell which branch to check out: `git lab checkout QUERY`, with a part of its name."));
+        }
+
+        let mut candidates = Switch::candidates(type_name, query)?;
+
+        let flow_branch = match candidates.len() {
+            0 => return Err(format!(
+                "No {} branch matches '{}' (remote branches are the ones of the last fetch).",
+                type_name.unwrap_or("flow"),
+                query
+            )),
+            1 => candidates.remove(0),
+            _ => Switch::choose(query, candidates)?
+        };
+
+        if GitV2::current_branch().as_deref() == Some(flow_branch.full_name.as_str()) {
+            info!("Already on {}", &flow_branch.full_name);
+            return Ok(());
+        }
+
+        working!("Checking out {}", &flow_branch.full_name);
+
+        // Only on the remote: a local branch tracking it is created
+        match flow_branch.local {
+            true => {
+                GitV2::checkout(None, &flow_branch.full_name, false)?;
+                success!("Switched to {}", &flow_branch.full_name);
+            },
+            false => {
+                GitV2::checkout_tracking(&flow_branch.full_name)?;
+                success!("Switched to {}, tracking origin/{}", &flow_branch.full_name, &flow_branch.full_name);
+            }
+        }
+
+        Ok(())
+
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::flow::branch::Branch;
+    use crate::flow::status::FlowBranch;
+
+    use super::{best_matches, matching, Match};
+
+    fn flow_branch (branch: Branch) -> FlowBranch {
+        FlowBranch { full_name: format!("{}/{}", branch.type_name(), branch.name()), branch, local: true, remote: false }
+    }
+
+    fn feature (name: &str) -> FlowBranch {
+        flow_branch(Branch::Feature(String::from(name)))
+    }
+
+    fn names (flow_branches: Vec<FlowBranch>) -> Vec<String> {
+        flow_branches.into_iter().map(|flow_branch| flow_branch.full_name).collect()
+    }
+
+    #[test]
+    fn full_name_or_name_match_exactly () {
+        assert_eq!(matching("feature/ABC-123-login", &feature("ABC-123-login")), Some(Match::Exact));
+        assert_eq!(matching("abc-123-LOGIN", &feature("ABC-123-login")), Some(Match::Exact));
+    }
+
+    #[test]
+    fn whole_parts_match_before_substrings () {
+
+        assert_eq!(matching("123", &feature("ABC-123-login")), Some(Match::Part));
+        assert_eq!(matching("123", &feature("ABC-1234-logout")), Some(Match::Substring));
+        assert_eq!(matching("1234", &feature("ABC-123-login")), None);
+
+        // Any of its occurrences being a whole part is enough
+        assert_eq!(matching("log", &feature("catalog-log")), Some(Match::Part));
+        assert_eq!(matching("log", &feature("ABC-123-login")), Some(Match::Substring));
+
+    }
+
+    #[test]
+    fn case_is_folded () {
+        assert_eq!(matching("LOGIN", &feature("ABC-123-login")), Some(Match::Part));
+        assert_eq!(matching("abc", &feature("ABC-123-login")), Some(Match::Part));
+        assert_eq!(matching("ÇÃO", &feature("acao-migração")), Some(Match::Substring));
+    }
+
+    #[test]
+    fn fuzzy_matches_keep_the_order_of_the_characters () {
+        assert_eq!(matching("a123lg", &feature("ABC-123-login")), Some(Match::Fuzzy));
+        assert_eq!(matching("fa1l", &feature("ABC-123-login")), Some(Match::Fuzzy));
+        assert_eq!(matching("lg123", &feature("ABC-123-login")), None);
+        assert_eq!(matching("xyz", &feature("ABC-123-login")), None);
+    }
+
+    #[test]
+    fn blank_queries_match_nothing () {
+        assert_eq!(matching("", &feature("ABC-123-login")), None);
+        assert_eq!(matching("  ", &feature("ABC-123-login")), None);
+        assert_eq!(matching(" login ", &feature("ABC-123-login")), Some(Match::Part));
+    }
+
+    #[test]
+    fn only_the_best_matches_are_kept () {
+
+        let branches = || vec![
+            feature("ABC-1234-logout"),
+            feature("ABC-123-login"),
+            flow_branch(Branch::Bugfix(String::from("ABC-123-crash"))),
+            feature("XYZ-9-payments"),
+        ];
+
+        assert_eq!(names(best_matches("123", branches())), vec!["feature/ABC-123-login", "bugfix/ABC-123-crash"]);
+        assert_eq!(names(best_matches("1234", branches())), vec!["feature/ABC-1234-logout"]);
+        assert_eq!(names(best_matches("pay", branches())), vec!["feature/XYZ-9-payments"]);
+        assert_eq!(names(best_matches("lgout", branches())), vec!["feature/ABC-1234-logout"]);
+        assert!(best_matches("nothing-like-it", branches()).is_empty());
+
+    }
+
+}
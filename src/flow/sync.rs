@@ -0,0 +1,140 @@
+use crate::command::gitv2::GitV2;
+use crate::flow::branch::Branch;
+use crate::flow::finish::Finish;
+use crate::{info, success, warning, working};
+
+///
+/// `git lab sync`: finishes, locally, the branches whose merge
+/// requests were merged on the remote server.
+///
+pub enum Synchronization {}
+
+impl Synchronization {
+
+    ///
+    /// Removes every local branch (and, unless `keep_remote`, its
+    /// remote branch) already merged into all of its targets on
+    /// the remote, then brings those targets up to date.
+    ///
+    pub fn run (keep_remote: bool) -> Result<(), String> {
+
+        if !GitV2::is_remote() {
+            return Err(String::from("There is no remote server to sync with."));
+        }
+
+        working!("Fetching from the remote");
+
+        GitV2::fetch_prune()?;
+
+        let mut pulled_targets: Vec<String> = Vec::new();
+
+        for branch_fullname in GitV2::local_branches()? {
+
+            let branch = match Branch::from(&branch_fullname) {
+                Some(branch @ (Branch::Feature(_) | Branch::Bugfix(_) | Branch::Hotfix(_) | Branch::Release(_) | Branch::Custom(_, _))) => branch,
+                _ => continue
+            };
+
+            let targets: Vec<String> = match branch.source() {
+                Ok(targets) => targets.iter().map(|target| target.full_name()).collect(),
+                Err(_) => continue
+            };
+
+            if !Synchronization::is_merged(&branch, &targets) {
+                continue;
+            }
+
+            Synchronization::remove(&branch, &targets, keep_remote)?;
+
+            targets
+                .into_iter()
+                .for_each(|target| if !pulled_targets.contains(&target) { pulled_targets.push(target) });
+
+        }
+
+        if pulled_targets.is_empty() {
+            info!("No merged branch to sync");
+            return Ok(());
+        }
+
+        Finish::pull_targets(&pulled_targets);
+
+        Ok(())
+
+    }
+
+    ///
+    /// Tells if the branch has work of its own and that work is
+    /// in every target on the remote.
+    ///
+    fn is_merged (branch: &Branch, targets: &[String]) -> bool {
+
+        let branch_fullname = branch.full_name();
+
+        // A branch just started is contained in its targets too
+        if !branch.has_work(&branch_fullname) {
+            return false;
+        }
+
+        let tip = match GitV2::rev_parse(&branch_fullname) {
+            Ok(tip) => tip,
+            Err(_) => return false
+        };
+
+        let remote_targets: Vec<String> = targets
+            .iter()
+            .map(|target| format!("origin/{}", target))
+            .filter(|remote_target| GitV2::rev_parse(remote_target).is_ok())
+            .collect();
+
+        !remote_targets.is_empty() && remote_targets
+            .iter()
+            .all(|remote_target| GitV2::is_ancestor(&tip, remote_target) || GitV2::is_content_merged(&tip, remote_target))
+
+    }
+
+    fn remove (branch: &Branch, targets: &[String], keep_remote: bool) -> Result<(), String> {
+
+        let branch_fullname = branch.full_name();
+
+        info!("The merge request of {} was merged", &branch_fullname);
+
+        if GitV2::current_branch().as_deref() == Some(branch_fullname.as_str()) {
+            GitV2::checkout(None, &branch.base()?, false)?;
+        }
+
+        // Already checked against the targets on the remote
+        GitV2::remove_local_branch(None, &branch_fullname, true)?;
+
+        success!("Branch {} removed from local", &branch_fullname);
+
+        if keep_remote {
+            return Ok(());
+        }
+
+        let commit = match GitV2::remote_commit(&branch_fullname) {
+            Some(commit) => commit,
+            // Usually removed by the merge request itself
+            None => return Ok(())
+        };
+
+        let is_merged = targets.iter().all(|target| {
+            let remote_target = format!("origin/{}", target);
+            GitV2::is_ancestor(&commit, &remote_target) || GitV2::is_content_merged(&commit, &remote_target)
+        });
+
+        if !is_merged {
+            warning!("Branch {} was kept on the remote: it has commits that weren't merged", &branch_fullname);
+            return Ok(());
+        }
+
+        match GitV2::remove_remote_branch(&branch_fullname) {
+            Ok(_) => { success!("Branch {} removed from the remote", &branch_fullname); },
+            Err(e) => { warning!("Couldn't remove {} from the remote: {}", &branch_fullname, e.trim()); }
+        }
+
+        Ok(())
+
+    }
+
+}
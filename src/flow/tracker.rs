@@ -0,0 +1,180 @@
+use crate::command::gitlab::{GitLab, MergeRequest};
+use crate::command::jira::Jira;
+use crate::config::constants::{
+    JIRA_START_STATUS_KEY,
+    JIRA_FINISH_STATUS_KEY,
+    DEFAULT_JIRA_START_STATUS,
+    DEFAULT_JIRA_FINISH_STATUS,
+};
+use crate::config::store::Store;
+use crate::flow::branch::Branch;
+use crate::flow::policy::NamingPolicy;
+use crate::{info, success, warning};
+
+///
+/// An issue a branch is started from, in Jira (`ABC-123`) or
+/// GitLab (`42`, or `#42`).
+///
+#[derive(Debug, PartialEq, Clone)]
+pub struct Issue {
+    pub key: String,
+    pub title: String,
+    /// The web page of the issue
+    pub url: Option<String>,
+    pub is_gitlab: bool,
+}
+
+impl Issue {
+
+    ///
+    /// Fetches the issue: numbers are GitLab issues of the project,
+    /// anything else Jira ones.
+    ///
+    pub fn fetch (key: &str) -> Result<Issue, String> {
+
+        let key = key.trim().trim_start_matches('#');
+
+        if !key.is_empty() && key.chars().all(|current| current.is_ascii_digit()) {
+            let (title, url) = GitLab::issue(key)?;
+            return Ok(Issue { key: key.to_string(), title, url, is_gitlab: true });
+        }
+
+        let jira = Jira::load()?;
+
+        Ok(Issue {
+            key: key.to_string(),
+            title: jira.summary(key)?,
+            url: Some(jira.browse_url(key)),
+            is_gitlab: false,
+        })
+
+    }
+
+    ///
+    /// The title of the merge requests of the branch.
+    ///
+    pub fn merge_request_title (&self) -> String {
+        match self.is_gitlab {
+            true => format!("Resolve \"{}\"", self.title),
+            false => format!("{} {}", self.key, self.title)
+        }
+    }
+
+    ///
+    /// The description of the merge requests of the branch: GitLab
+    /// issues are closed by them, Jira ones are linked.
+    ///
+    pub fn merge_request_description (&self) -> String {
+        match (self.is_gitlab, &self.url) {
+            (true, _) => format!("Closes #{}", self.key),
+            (false, Some(url)) => format!("[{}]({}) {}", self.key, url, self.title),
+            (false, None) => format!("{} {}", self.key, self.title)
+        }
+    }
+
+}
+
+///
+/// Keeps the issue keyed in the branch name (`feature/ABC-123-login`)
+/// in step with the flow, when an issue tracker is configured: it
+/// moves along on start and finish, and gets the merge requests
+/// linked. The tracker being down never stops the flow, it is
+/// only reported.
+///
+pub enum IssueTracker {}
+
+fn status (key: &str, default_status: &str) -> String {
+    match Store::get(key) {
+        Ok(status) if !status.trim().is_empty() => String::from(status.trim()),
+        _ => String::from(default_status)
+    }
+}
+
+impl IssueTracker {
+
+    ///
+    /// The tracker and the key of the issue of the branch, if
+    /// both are there.
+    ///
+    fn issue (branch: &Branch) -> Option<(Jira, String)> {
+
+        let jira = Jira::load().ok()?;
+
+        let issue_key = NamingPolicy::issue_key(branch.name());
+
+        if issue_key.is_none() {
+            info!("There is no issue key in {}: the issue tracker was left untouched", branch.full_name());
+        }
+
+        issue_key.map(|issue_key| (jira, issue_key))
+
+    }
+
+    fn transition (jira: &Jira, issue_key: &str, status: &str) {
+        match jira.transition(issue_key, status) {
+            Ok(_) => { success!("Issue {} moved to {}", issue_key, status); },
+            Err(e) => { warning!("Couldn't move issue {} to {}: {}", issue_key, status, e.trim()); }
+        }
+    }
+
+    ///
+    /// The branch was started: its issue is in progress.
+    ///
+    pub fn started (branch: &Branch) {
+        if let Some((jira, issue_key)) = IssueTracker::issue(branch) {
+            IssueTracker::transition(&jira, &issue_key, &status(JIRA_START_STATUS_KEY, DEFAULT_JIRA_START_STATUS));
+        }
+    }
+
+    ///
+    /// The branch was finished, with merge requests opened into
+    /// the given targets: once they are on GitLab, they are linked
+    /// to its issue and the issue is in review. Merged locally, or
+    /// with none of them submitted yet, the issue is left as it is.
+    ///
+    pub fn finished (branch: &Branch, merge_request_targets: &[String]) {
+
+        let (jira, issue_key) = match IssueTracker::issue(branch) {
+            Some(issue) => issue,
+            None => return
+        };
+
+        if merge_request_targets.is_empty() {
+            info!("No merge request was opened: issue {} was left untouched", &issue_key);
+            return;
+        }
+
+        // The pages opened in the browser only make merge requests once submitted
+        let merge_requests: Vec<MergeRequest> = match GitLab::open_merge_requests(&branch.full_name()) {
+            Ok(merge_requests) => merge_requests.into_iter().filter(|merge_request| merge_request_targets.contains(&merge_request.target)).collect(),
+            Err(e) => {
+                warning!("Couldn't find the merge requests of {} on GitLab: issue {} was left untouched ({})", branch.full_name(), &issue_key, e.trim());
+                return;
+            }
+        };
+
+        if merge_requests.is_empty() {
+            info!(
+                "No merge request of {} is open on GitLab yet: issue {} wasn't moved nor linked to one. Move it once the merge request is submitted.",
+                branch.full_name(),
+                &issue_key
+            );
+            return;
+        }
+
+        for merge_request in merge_requests.iter() {
+
+            let title = format!("Merge request of {} into {}", branch.full_name(), &merge_request.target);
+
+            match jira.add_link(&issue_key, &merge_request.url, &title) {
+                Ok(_) => { success!("Merge request into {} linked to issue {}", &merge_request.target, &issue_key); },
+                Err(e) => { warning!("Couldn't link the merge request into {} to issue {}: {}", &merge_request.target, &issue_key, e.trim()); }
+            }
+
+        }
+
+        IssueTracker::transition(&jira, &issue_key, &status(JIRA_FINISH_STATUS_KEY, DEFAULT_JIRA_FINISH_STATUS));
+
+    }
+
+}
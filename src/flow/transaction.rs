@@ -0,0 +1,197 @@
+use std::fmt::Display;
+
+use crate::command::gitv2::GitV2;
+use crate::flow::branch::Branch;
+
+///
+/// A side effect of a finish (or a rename), with what is needed to undo it:
+/// the refs it touches are snapshotted before it runs.
+///
+#[derive(Debug, PartialEq, Clone)]
+pub enum Step {
+    /// A commit on the branch (the changelog of a release)
+    Commit { branch: String, before: String },
+    /// The branch pushed to the remote, where it pointed to `before` (if it existed)
+    Push { branch: String, before: Option<String> },
+    /// The page of a merge request opened
+    MergeRequest { branch: String, target: String },
+    /// The branch merged into the target (the rebase strategy rewrites the branch too)
+    Merge { branch: String, branch_before: String, target: String, target_before: String },
+    /// A tag created
+    Tag { name: String },
+    /// The branch deleted, along with the base recorded for it
+    Delete { branch: String, commit: String, base: Option<String> },
+    /// The branch deleted from the remote, where it pointed to the given commit
+    RemoteDelete { branch: String, commit: String },
+    /// The local branch renamed
+    Rename { from: String, to: String },
+}
+
+fn short (commit: &str) -> &str {
+    commit.get(..7).unwrap_or(commit)
+}
+
+impl Step {
+
+    ///
+    /// Undoes the step, telling what was undone. Gives an error
+    /// when it can't be undone.
+    ///
+    /// Branches are moved with `git update-ref`, so none of them
+    /// may be checked out while undoing.
+    ///
+    pub fn undo (&self) -> Result<String, String> {
+
+        match self {
+
+            Step::Commit { branch, before } => {
+                GitV2::update_ref(&format!("refs/heads/{}", branch), before)?;
+                Ok(format!("commit on {} removed ({} back to {})", branch, branch, short(before)))
+            },
+
+            Step::Push { branch, before: Some(before) } => {
+                GitV2::push_commit(before, branch)?;
+                Ok(format!("push of {} undone (remote back to {})", branch, short(before)))
+            },
+
+            Step::Push { branch, before: None } => {
+                GitV2::remove_remote_branch(branch)?;
+                Ok(format!("{} removed from the remote", branch))
+            },
+
+            Step::MergeRequest { branch, target } => Err(format!(
+                "the merge request page of {} into {} was opened: close the merge request if it was created",
+                branch,
+                target
+            )),
+
+            Step::Merge { branch, branch_before, target, target_before } => {
+                GitV2::update_ref(&format!("refs/heads/{}", target), target_before)?;
+                GitV2::update_ref(&format!("refs/heads/{}", branch), branch_before)?;
+                Ok(format!("merge of {} into {} undone ({} back to {})", branch, target, target, short(target_before)))
+            },
+
+            Step::Tag { name } => {
+                GitV2::delete_tag(name)?;
+                Ok(format!("tag {} removed", name))
+            },
+
+            Step::Delete { branch, commit, base } => {
+
+                GitV2::update_ref(&format!("refs/heads/{}", branch), commit)?;
+
+                if let (Some(base), Some(restored)) = (base, Branch::from(branch)) {
+                    restored.record_base(base)?;
+                }
+
+                Ok(format!("branch {} restored at {}", branch, short(commit)))
+
+            },
+
+            Step::RemoteDelete { branch, commit } => {
+                GitV2::push_commit(commit, branch)?;
+                Ok(format!("{} pushed back to the remote at {}", branch, short(commit)))
+            },
+
+            Step::Rename { from, to } => {
+                GitV2::rename_branch(to, from)?;
+                Ok(format!("{} renamed back to {}", to, from))
+            },
+
+        }
+
+    }
+
+    ///
+    /// Reads the step from its line in the finish progress file.
+    ///
+    pub fn parse (line: &str) -> Option<Step> {
+
+        let fields: Vec<&str> = line.split(' ').collect();
+
+        // "-" stands for nothing
+        let optional = |field: &str| match field {
+            "-" => None,
+            _ => Some(field.to_string())
+        };
+
+        match fields.as_slice() {
+            ["commit", branch, before] => Some(Step::Commit { branch: branch.to_string(), before: before.to_string() }),
+            ["push", branch, before] => Some(Step::Push { branch: branch.to_string(), before: optional(before) }),
+            ["mergerequest", branch, target] => Some(Step::MergeRequest { branch: branch.to_string(), target: target.to_string() }),
+            ["merge", branch, branch_before, target, target_before] => Some(Step::Merge {
+                branch: branch.to_string(),
+                branch_before: branch_before.to_string(),
+                target: target.to_string(),
+                target_before: target_before.to_string(),
+            }),
+            ["tag", name] => Some(Step::Tag { name: name.to_string() }),
+            ["delete", branch, commit, base] => Some(Step::Delete { branch: branch.to_string(), commit: commit.to_string(), base: optional(base) }),
+            ["remotedelete", branch, commit] => Some(Step::RemoteDelete { branch: branch.to_string(), commit: commit.to_string() }),
+            ["rename", from, to] => Some(Step::Rename { from: from.to_string(), to: to.to_string() }),
+            _ => None
+        }
+
+    }
+
+}
+
+///
+/// The line of the step in the finish progress file.
+///
+impl Display for Step {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Step::Commit { branch, before } => write!(f, "commit {} {}", branch, before),
+            Step::Push { branch, before } => write!(f, "push {} {}", branch, before.as_deref().unwrap_or("-")),
+            Step::MergeRequest { branch, target } => write!(f, "mergerequest {} {}", branch, target),
+            Step::Merge { branch, branch_before, target, target_before } => write!(f, "merge {} {} {} {}", branch, branch_before, target, target_before),
+            Step::Tag { name } => write!(f, "tag {}", name),
+            Step::Delete { branch, commit, base } => write!(f, "delete {} {} {}", branch, commit, base.as_deref().unwrap_or("-")),
+            Step::RemoteDelete { branch, commit } => write!(f, "remotedelete {} {}", branch, commit),
+            Step::Rename { from, to } => write!(f, "rename {} {}", from, to),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::Step;
+
+    #[test]
+    fn every_step_is_read_back_from_its_line () {
+
+        let steps = vec![
+            Step::Commit { branch: String::from("release/1.2.0"), before: String::from("0123456789abcdef") },
+            Step::Push { branch: String::from("feature/ABC-1-add-login"), before: Some(String::from("0123456")) },
+            Step::Push { branch: String::from("feature/ABC-1-add-login"), before: None },
+            Step::MergeRequest { branch: String::from("feature/a=b"), target: String::from("develop") },
+            Step::Merge {
+                branch: String::from("hotfix/1.2.1"),
+                branch_before: String::from("0123456"),
+                target: String::from("main"),
+                target_before: String::from("789abcd"),
+            },
+            Step::Tag { name: String::from("1.2.0-rc.1+build.7") },
+            Step::Delete { branch: String::from("bugfix/a-b--c"), commit: String::from("0123456"), base: Some(String::from("release/1.2.0")) },
+            Step::Delete { branch: String::from("bugfix/a-b--c"), commit: String::from("0123456"), base: None },
+            Step::RemoteDelete { branch: String::from("feature/x_y.z"), commit: String::from("0123456") },
+            Step::Rename { from: String::from("feature/old"), to: String::from("feature/new-name") },
+        ];
+
+        for step in steps {
+            assert_eq!(Step::parse(&step.to_string()), Some(step.clone()), "{}", step);
+        }
+
+    }
+
+    #[test]
+    fn lines_with_missing_or_extra_fields_are_not_steps () {
+        assert_eq!(Step::parse(""), None);
+        assert_eq!(Step::parse("commit release/1.2.0"), None);
+        assert_eq!(Step::parse("tag 1.2.0 extra"), None);
+        assert_eq!(Step::parse("squash feature/a develop"), None);
+    }
+
+}
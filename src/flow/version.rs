@@ -0,0 +1,386 @@
+use std::cmp::Ordering;
+use std::fmt::Display;
+
+use regex::Regex;
+
+use crate::config::constants::{
+    SEMVER_REGEX_PATTERN,
+    TAG_PREFIX_KEY,
+    DEFAULT_PRE_RELEASE_ID,
+};
+
+use crate::config::store::Store;
+use crate::command::gitv2::GitV2;
+
+///
+/// Which part of the version is incremented.
+///
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Bump {
+    Major,
+    Minor,
+    Patch,
+    Pre,
+}
+
+impl Bump {
+
+    pub fn parse (text: &str) -> Option<Bump> {
+        match text.trim().to_lowercase().as_str() {
+            "major" => Some(Bump::Major),
+            "minor" => Some(Bump::Minor),
+            "patch" => Some(Bump::Patch),
+            "pre" => Some(Bump::Pre),
+            _ => None
+        }
+    }
+
+}
+
+///
+/// A semantic version (https://semver.org) used to name
+/// releases and hotfixes. Build metadata (`+build.5`) is
+/// accepted but dropped: it doesn't order versions.
+///
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Version {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+    pub pre: Option<String>,
+}
+
+///
+/// The configured tag prefix (like `v`), or nothing.
+///
+pub fn tag_prefix () -> String {
+    match Store::get(TAG_PREFIX_KEY) {
+        Ok(prefix) => String::from(prefix.trim()),
+        Err(_) => String::new()
+    }
+}
+
+impl Version {
+
+    ///
+    /// Parses the given text as a semantic version. The
+    /// configured tag prefix is accepted (and ignored).
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// let version = Version::parse("1.4.0-rc.1");
+    /// ```
+    ///
+    pub fn parse (text: &str) -> Option<Version> {
+        Version::parse_with_prefix(text, &tag_prefix())
+    }
+
+    fn parse_with_prefix (text: &str, prefix: &str) -> Option<Version> {
+
+        let text = text.trim();
+        let text = match text.strip_prefix(prefix) {
+            Some(rest) if !prefix.is_empty() => rest,
+            _ => text
+        };
+
+        let regex = match Regex::new(SEMVER_REGEX_PATTERN) {
+            Ok(regex) => regex,
+            Err(_) => return None
+        };
+
+        let captures = regex.captures(text)?;
+
+        Some(Version {
+            major: captures["major"].parse().ok()?,
+            minor: captures["minor"].parse().ok()?,
+            patch: captures["patch"].parse().ok()?,
+            pre: captures.name("pre").map(|pre| pre.as_str().to_string()),
+        })
+
+    }
+
+    ///
+    /// The latest version tagged on the given branch, if any.
+    ///
+    pub fn latest (branch_name: &str) -> Result<Option<Version>, String> {
+        Ok(Version::latest_tagged(branch_name)?.map(|(version, _)| version))
+    }
+
+    ///
+    /// The tag of the latest version on the given branch, if any.
+    ///
+    pub fn latest_tag (branch_name: &str) -> Result<Option<String>, String> {
+        Ok(Version::latest_tagged(branch_name)?.map(|(_, tag)| tag))
+    }
+
+    fn latest_tagged (branch_name: &str) -> Result<Option<(Version, String)>, String> {
+
+        let prefix = tag_prefix();
+
+        Ok(
+            GitV2::tags(branch_name)?
+                .iter()
+                .filter(|tag| tag.starts_with(prefix.as_str()))
+                .filter_map(|tag| Version::parse_with_prefix(tag, &prefix).map(|version| (version, tag.clone())))
+                .max()
+        )
+
+    }
+
+    ///
+    /// The version following the latest one tagged on the
+    /// given branch (starting from 0.0.0 when there's none).
+    ///
+    pub fn next (branch_name: &str, bump: Bump) -> Result<Version, String> {
+
+        let latest = match Version::latest(branch_name)? {
+            Some(version) => version,
+            None => Version { major: 0, minor: 0, patch: 0, pre: None }
+        };
+
+        Ok(latest.bump(bump))
+
+    }
+
+    ///
+    /// The tag name for the given release or hotfix name:
+    /// the tag prefix plus the version, when it is one.
+    ///
+    pub fn tag_name (name: &str) -> String {
+        match Version::parse(name) {
+            Some(version) => format!("{}{}", tag_prefix(), version),
+            None => name.to_string()
+        }
+    }
+
+    ///
+    /// Increments the given part of the version. Bumping
+    /// a pre-release to the version it precedes drops the
+    /// pre-release part (1.2.0-rc.2 minor bump is 1.2.0).
+    ///
+    pub fn bump (&self, bump: Bump) -> Version {
+
+        let is_pre = self.pre.is_some();
+
+        match bump {
+
+            Bump::Major => {
+                if is_pre && self.minor == 0 && self.patch == 0 {
+                    Version { pre: None, ..self.clone() }
+                } else {
+                    Version { major: self.major + 1, minor: 0, patch: 0, pre: None }
+                }
+            },
+
+            Bump::Minor => {
+                if is_pre && self.patch == 0 {
+                    Version { pre: None, ..self.clone() }
+                } else {
+                    Version { major: self.major, minor: self.minor + 1, patch: 0, pre: None }
+                }
+            },
+
+            Bump::Patch => {
+                if is_pre {
+                    Version { pre: None, ..self.clone() }
+                } else {
+                    Version { patch: self.patch + 1, ..self.clone() }
+                }
+            },
+
+            Bump::Pre => match &self.pre {
+                Some(pre) => Version { pre: Some(next_pre_release(pre)), ..self.clone() },
+                None => Version {
+                    patch: self.patch + 1,
+                    pre: Some(format!("{}.1", DEFAULT_PRE_RELEASE_ID)),
+                    ..self.clone()
+                }
+            },
+
+        }
+
+    }
+
+}
+
+///
+/// Increments the last numeric identifier of the pre-release
+/// (rc.1 becomes rc.2) or appends one (beta becomes beta.1).
+///
+fn next_pre_release (pre: &str) -> String {
+
+    let mut identifiers: Vec<String> = pre.split('.').map(|identifier| identifier.to_string()).collect();
+
+    match identifiers.last().and_then(|last| last.parse::<u64>().ok()) {
+        Some(number) => {
+            identifiers.pop();
+            identifiers.push((number + 1).to_string());
+        },
+        None => identifiers.push(String::from("1"))
+    }
+
+    identifiers.join(".")
+
+}
+
+///
+/// Compares pre-release parts following the semver rules:
+/// numeric identifiers are compared as numbers and have
+/// lower precedence than alphanumeric ones.
+///
+fn compare_pre_release (left: &str, right: &str) -> Ordering {
+
+    let mut left_identifiers = left.split('.');
+    let mut right_identifiers = right.split('.');
+
+    loop {
+        match (left_identifiers.next(), right_identifiers.next()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(left), Some(right)) => {
+                let ordering = match (left.parse::<u64>(), right.parse::<u64>()) {
+                    (Ok(left), Ok(right)) => left.cmp(&right),
+                    (Ok(_), Err(_)) => Ordering::Less,
+                    (Err(_), Ok(_)) => Ordering::Greater,
+                    (Err(_), Err(_)) => left.cmp(right),
+                };
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+        }
+    }
+
+}
+
+impl Ord for Version {
+    fn cmp (&self, other: &Self) -> Ordering {
+        self.major.cmp(&other.major)
+            .then(self.minor.cmp(&other.minor))
+            .then(self.patch.cmp(&other.patch))
+            .then(match (&self.pre, &other.pre) {
+                (None, None) => Ordering::Equal,
+                // A pre-release comes before its release
+                (None, Some(_)) => Ordering::Greater,
+                (Some(_), None) => Ordering::Less,
+                (Some(left), Some(right)) => compare_pre_release(left, right),
+            })
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp (&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Display for Version {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.pre {
+            Some(pre) => write!(f, "{}.{}.{}-{}", self.major, self.minor, self.patch, pre),
+            None => write!(f, "{}.{}.{}", self.major, self.minor, self.patch),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::cmp::Ordering;
+
+    use super::{Bump, Version};
+
+    fn version (text: &str) -> Version {
+        Version::parse_with_prefix(text, "").unwrap()
+    }
+
+    #[test]
+    fn versions_are_parsed () {
+
+        assert_eq!(version("1.4.0"), Version { major: 1, minor: 4, patch: 0, pre: None });
+        assert_eq!(version(" 10.20.30-rc.1 "), Version { major: 10, minor: 20, patch: 30, pre: Some(String::from("rc.1")) });
+        assert_eq!(version("1.0.0-alpha-2.x.7+build.5"), Version { major: 1, minor: 0, patch: 0, pre: Some(String::from("alpha-2.x.7")) });
+        assert_eq!(version("1.2.3+20260102"), version("1.2.3"));
+
+        for invalid in ["1.4", "1.4.0.1", "01.4.0", "1.4.0-", "1.4.0-rc..1", "1.4.0+", "v1.4.0", "one.two.three", ""] {
+            assert_eq!(Version::parse_with_prefix(invalid, ""), None, "{}", invalid);
+        }
+
+    }
+
+    #[test]
+    fn tag_prefix_is_accepted () {
+        assert_eq!(Version::parse_with_prefix("v1.4.0", "v"), Some(version("1.4.0")));
+        assert_eq!(Version::parse_with_prefix("1.4.0", "v"), Some(version("1.4.0")));
+        assert_eq!(Version::parse_with_prefix("release-1.4.0", "v"), None);
+    }
+
+    #[test]
+    fn bumps_reset_the_lower_parts () {
+
+        let current = version("1.4.2");
+
+        assert_eq!(current.bump(Bump::Major), version("2.0.0"));
+        assert_eq!(current.bump(Bump::Minor), version("1.5.0"));
+        assert_eq!(current.bump(Bump::Patch), version("1.4.3"));
+        assert_eq!(current.bump(Bump::Pre), version("1.4.3-rc.1"));
+
+    }
+
+    #[test]
+    fn bumps_clear_the_pre_release_and_build () {
+
+        assert_eq!(version("2.0.0-rc.2").bump(Bump::Major), version("2.0.0"));
+        assert_eq!(version("2.1.0-rc.2").bump(Bump::Major), version("3.0.0"));
+        assert_eq!(version("1.2.0-beta").bump(Bump::Minor), version("1.2.0"));
+        assert_eq!(version("1.2.1-beta").bump(Bump::Minor), version("1.3.0"));
+        assert_eq!(version("1.2.1-beta").bump(Bump::Patch), version("1.2.1"));
+        assert_eq!(version("1.2.1+build.9").bump(Bump::Patch).to_string(), "1.2.2");
+
+    }
+
+    #[test]
+    fn pre_release_bumps_count_up () {
+        assert_eq!(version("1.2.0-rc.9").bump(Bump::Pre), version("1.2.0-rc.10"));
+        assert_eq!(version("1.2.0-beta").bump(Bump::Pre), version("1.2.0-beta.1"));
+        assert_eq!(version("1.2.0-beta.x").bump(Bump::Pre), version("1.2.0-beta.x.1"));
+    }
+
+    #[test]
+    fn pre_releases_come_before_their_release () {
+        assert!(version("1.0.0-rc.1") < version("1.0.0"));
+        assert!(version("1.0.0") < version("1.0.1-alpha"));
+        assert_eq!(version("1.0.0+a").cmp(&version("1.0.0+b")), Ordering::Equal);
+    }
+
+    #[test]
+    fn versions_are_ordered_as_in_semver () {
+
+        // The example of https://semver.org (11.4.4)
+        let ordered = [
+            "1.0.0-alpha", "1.0.0-alpha.1", "1.0.0-alpha.beta", "1.0.0-beta", "1.0.0-beta.2",
+            "1.0.0-beta.11", "1.0.0-rc.1", "1.0.0", "1.9.0", "1.10.0", "2.0.0",
+        ];
+
+        for pair in ordered.windows(2) {
+            assert!(version(pair[0]) < version(pair[1]), "{} < {}", pair[0], pair[1]);
+        }
+
+        let mut shuffled: Vec<Version> = ordered.iter().rev().map(|text| version(text)).collect();
+        shuffled.sort();
+
+        assert_eq!(shuffled, ordered.iter().map(|text| version(text)).collect::<Vec<Version>>());
+
+    }
+
+    #[test]
+    fn numeric_identifiers_are_compared_as_numbers_and_come_first () {
+        assert!(version("1.0.0-rc.2") < version("1.0.0-rc.10"));
+        assert!(version("1.0.0-2") < version("1.0.0-10"));
+        assert!(version("1.0.0-999") < version("1.0.0-a"));
+        assert!(version("1.0.0-rc.1") < version("1.0.0-rc.a"));
+    }
+
+}
@@ -5,29 +5,61 @@ mod config;
 mod flow;
 
 use command::args::{Action};
+use command::gitv2::GitV2;
+use flow::preflight::Preflight;
+use config::error::ACTION_ERROR_CODE;
 
-fn inspect_action () -> Option<Action> {
+fn inspect_action (args: &Vec<String>) -> Option<Action> {
 
-    let args: Vec<String> = std::env::args().collect();
-
-    Action::new(&args)
+    Action::new(args)
 
 }
 
 fn main() {
 
-    println!("\r\n#########");
-    println!("Lab Flow");
-    println!("#########\r\n");
+    let args: Vec<String> = std::env::args().collect();
 
-    match inspect_action() {
+    // Output for scripts has nothing but the result
+    config::log::set_quiet(Action::is_quiet(&args));
+
+    if !config::log::is_quiet() {
+        println!("\r\n#########");
+        println!("Lab Flow");
+        println!("#########\r\n");
+    }
+
+    match inspect_action(&args) {
         Some(action) => { 
-            match action.execute() {
+
+            let stash = match action.preflight() {
+                Ok(stash) => stash,
+                Err(e) => {
+                    error!("{}", e);
+                    std::process::exit(e.code());
+                }
+            };
+
+            let stashed_from = GitV2::current_branch();
+
+            let result = action.execute();
+
+            match &result {
                 Ok(_) => success!("Done!\r\n"),
                 Err(e) => {
                     error!("{}", e);
                 }
             } 
+
+            if let Some(stash) = stash {
+                if let Err(e) = Preflight::restore_stash(&stash, stashed_from.as_deref(), result.is_ok()) {
+                    error!("{}", e);
+                }
+            }
+
+            if result.is_err() {
+                std::process::exit(ACTION_ERROR_CODE);
+            }
+
         },
         None => {}
     }
@@ -0,0 +1,201 @@
+#![allow(dead_code)]
+
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+///
+/// A scratch repository, set up for lab flow, where the
+/// `git-flow` binary is run. It is removed when dropped.
+///
+pub struct Repo {
+    pub path: PathBuf,
+    /// The bare repository of `origin`, if there is one
+    pub remote: Option<PathBuf>,
+}
+
+fn scratch (name: &str) -> PathBuf {
+
+    let path = std::env::temp_dir().join(format!("labflow-test-{}-{}", name, std::process::id()));
+
+    let _ = fs::remove_dir_all(&path);
+
+    fs::create_dir_all(&path).unwrap();
+
+    path
+
+}
+
+fn command (program: &str, dir: &Path) -> Command {
+
+    let mut command = Command::new(program);
+
+    command
+        .current_dir(dir)
+        .env("GIT_CONFIG_NOSYSTEM", "1")
+        .env("GIT_CONFIG_GLOBAL", "/dev/null")
+        .env("GIT_AUTHOR_DATE", "2026-01-02T10:00:00+00:00")
+        .env("GIT_COMMITTER_DATE", "2026-01-02T10:00:00+00:00")
+        // The fake browser first
+        .env("PATH", format!("{}:{}", dir.join(".git").join("test-bin").display(), std::env::var("PATH").unwrap_or_default()));
+
+    command
+
+}
+
+impl Repo {
+
+    ///
+    /// A repository with `main` and `develop`, initiated.
+    ///
+    pub fn new (name: &str) -> Repo {
+
+        let repo = Repo { path: scratch(name), remote: None };
+
+        repo.git(&["init", "-q", "-b", "main"]);
+        repo.git(&["config", "user.name", "Tester"]);
+        repo.git(&["config", "user.email", "tester@acme.com"]);
+
+        for type_name in ["feature", "bugfix", "hotfix", "release", "support"] {
+            repo.git(&["config", &format!("lab.flow.branch.{}", type_name), &format!("{}/", type_name)]);
+        }
+
+        repo.git(&["config", "lab.flow.branch.develop", "develop"]);
+        repo.git(&["config", "lab.flow.branch.main", "main"]);
+
+        repo.git(&["commit", "-q", "--allow-empty", "-m", "chore: init"]);
+        repo.git(&["branch", "develop"]);
+
+        // The merge request pages "opened" here go nowhere
+        let bin = repo.path.join(".git").join("test-bin");
+        fs::create_dir_all(&bin).unwrap();
+        repo.script(&bin.join("xdg-open"), "exit 0");
+
+        repo
+
+    }
+
+    ///
+    /// A repository with an `origin` (a bare one, next to it)
+    /// having `main` and `develop`.
+    ///
+    pub fn with_remote (name: &str) -> Repo {
+
+        let mut repo = Repo::new(name);
+
+        let remote = scratch(&format!("{}-remote", name));
+
+        command("git", &remote).args(["init", "-q", "--bare"]).output().unwrap();
+
+        repo.git(&["remote", "add", "origin", &remote.display().to_string()]);
+        repo.git(&["push", "-q", "-u", "origin", "main", "develop"]);
+
+        repo.remote = Some(remote);
+
+        repo
+
+    }
+
+    ///
+    /// Runs git, which must succeed, giving its output.
+    ///
+    pub fn git (&self, args: &[&str]) -> String {
+
+        let output = command("git", &self.path).args(args).output().unwrap();
+
+        assert!(output.status.success(), "git {:?} failed: {}", args, String::from_utf8_lossy(&output.stderr));
+
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+
+    }
+
+    ///
+    /// Tells if the git command succeeds.
+    ///
+    pub fn git_ok (&self, args: &[&str]) -> bool {
+        command("git", &self.path).args(args).output().unwrap().status.success()
+    }
+
+    ///
+    /// Runs git, giving all it did (the output of its hooks too).
+    ///
+    pub fn git_output (&self, args: &[&str]) -> Output {
+        command("git", &self.path).args(args).output().unwrap()
+    }
+
+    ///
+    /// Runs `git-flow` (as `git lab` would).
+    ///
+    pub fn lab (&self, args: &[&str]) -> Output {
+        command(env!("CARGO_BIN_EXE_git-flow"), &self.path).args(args).output().unwrap()
+    }
+
+    ///
+    /// Runs `git-flow`, which must succeed, giving its output.
+    ///
+    pub fn lab_ok (&self, args: &[&str]) -> String {
+
+        let output = self.lab(args);
+
+        assert!(output.status.success(), "git lab {:?} failed: {}", args, Repo::text(&output));
+
+        Repo::text(&output)
+
+    }
+
+    ///
+    /// Everything printed.
+    ///
+    pub fn text (output: &Output) -> String {
+        format!("{}{}", String::from_utf8_lossy(&output.stdout), String::from_utf8_lossy(&output.stderr))
+    }
+
+    pub fn write (&self, file: &str, content: &str) {
+
+        let path = self.path.join(file);
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).unwrap();
+        }
+
+        fs::write(path, content).unwrap();
+
+    }
+
+    pub fn read (&self, file: &str) -> String {
+        fs::read_to_string(self.path.join(file)).unwrap_or_default()
+    }
+
+    pub fn exists (&self, file: &str) -> bool {
+        self.path.join(file).exists()
+    }
+
+    ///
+    /// Commits the file with the given content on the branch
+    /// checked out.
+    ///
+    pub fn commit (&self, file: &str, content: &str, message: &str) {
+        self.write(file, content);
+        self.git(&["add", file]);
+        self.git(&["commit", "-q", "-m", message]);
+    }
+
+    ///
+    /// Writes an executable shell script.
+    ///
+    pub fn script (&self, path: &Path, body: &str) {
+        fs::write(path, format!("#!/bin/sh\n{}\n", body)).unwrap();
+        fs::set_permissions(path, fs::Permissions::from_mode(0o755)).unwrap();
+    }
+
+}
+
+impl Drop for Repo {
+    fn drop (&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+        if let Some(remote) = &self.remote {
+            let _ = fs::remove_dir_all(remote);
+        }
+    }
+}
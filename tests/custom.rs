@@ -0,0 +1,87 @@
+mod common;
+
+use common::Repo;
+
+///
+/// A `chore` type, merged locally, with a branch having one
+/// commit, finished.
+///
+fn finished_chore (name: &str, settings: &[(&str, &str)]) -> Repo {
+
+    let repo = Repo::new(name);
+
+    repo.git(&["config", "lab.flow.type.chore.prefix", "chore/"]);
+    repo.git(&["config", "lab.flow.type.chore.mergerequest", "false"]);
+
+    for (key, value) in settings {
+        repo.git(&["config", key, value]);
+    }
+
+    repo.lab_ok(&["chore", "start", "tidy"]);
+    repo.commit("a.txt", "tidy\n", "chore: tidy");
+    repo.lab_ok(&["chore", "finish", "tidy"]);
+
+    repo
+
+}
+
+fn parents (repo: &Repo, commit: &str) -> usize {
+    repo.git(&["rev-list", "--parents", "-n", "1", commit]).split_whitespace().count() - 1
+}
+
+#[test]
+fn custom_types_merge_with_a_merge_commit_by_default () {
+
+    let repo = finished_chore("custom-default", &[]);
+
+    assert_eq!(parents(&repo, "develop"), 2);
+
+}
+
+#[test]
+fn custom_types_follow_the_default_strategy () {
+
+    let repo = finished_chore("custom-configured-default", &[("lab.flow.merge.default", "ff")]);
+
+    assert_eq!(parents(&repo, "develop"), 1);
+    assert_eq!(repo.git(&["log", "-1", "--format=%s", "develop"]), "chore: tidy");
+
+}
+
+#[test]
+fn custom_types_follow_the_strategy_of_their_type () {
+
+    let repo = finished_chore("custom-type-strategy", &[("lab.flow.merge.default", "ff"), ("lab.flow.merge.chore", "squash")]);
+
+    assert_eq!(parents(&repo, "develop"), 1);
+    assert_ne!(repo.git(&["log", "-1", "--format=%s", "develop"]), "chore: tidy");
+    assert_eq!(repo.git(&["show", "develop:a.txt"]), "tidy");
+
+}
+
+#[test]
+fn custom_types_prefer_their_own_strategy () {
+
+    let repo = finished_chore("custom-own-strategy", &[("lab.flow.merge.chore", "squash"), ("lab.flow.type.chore.strategy", "no-ff")]);
+
+    assert_eq!(parents(&repo, "develop"), 2);
+
+}
+
+#[test]
+fn custom_types_named_like_a_command_are_refused () {
+
+    let repo = Repo::new("custom-command-name");
+
+    repo.git(&["config", "lab.flow.type.status.prefix", "status/"]);
+    repo.git(&["config", "lab.flow.type.chore.prefix", "chore/"]);
+
+    let init = repo.lab_ok(&["init"]);
+
+    assert!(init.contains("The branch type 'status' is named like the `git lab status` command"), "{}", init);
+    assert!(init.contains("The chore branch prefix is chore/"), "{}", init);
+
+    // The command is still the command
+    repo.lab_ok(&["status"]);
+
+}
@@ -0,0 +1,245 @@
+mod common;
+
+use common::Repo;
+
+const FINISH_STATE: &str = ".git/labflow/finish";
+
+///
+/// A feature and develop changing the same line, with `b.txt`
+/// left alone by both.
+///
+fn conflicting (name: &str) -> Repo {
+
+    let repo = Repo::new(name);
+
+    repo.git(&["checkout", "-q", "develop"]);
+    repo.commit("b.txt", "b\n", "chore: add b");
+
+    repo.lab_ok(&["feature", "start", "conflict"]);
+    repo.commit("a.txt", "feature\n", "feat: change a");
+
+    repo.git(&["checkout", "-q", "develop"]);
+    repo.commit("a.txt", "develop\n", "chore: change a");
+    repo.git(&["checkout", "-q", "feature/conflict"]);
+
+    repo
+
+}
+
+///
+/// Finishing the conflicting feature stops on conflicts,
+/// resolved (and staged) here.
+///
+fn conflicted (name: &str) -> Repo {
+
+    let repo = conflicting(name);
+
+    let output = repo.lab(&["feature", "finish", "conflict"]);
+
+    assert!(!output.status.success());
+    assert!(repo.exists(FINISH_STATE), "{}", Repo::text(&output));
+
+    repo.write("a.txt", "both\n");
+    repo.git(&["add", "a.txt"]);
+
+    repo
+
+}
+
+#[test]
+fn continue_keeps_the_finish_when_the_merge_commit_fails () {
+
+    let repo = conflicted("continue-fails");
+
+    // The merge commit is refused while the flag is there
+    let hooks = repo.path.join(".git").join("test-hooks");
+    std::fs::create_dir_all(&hooks).unwrap();
+    repo.script(&hooks.join("commit-msg"), "test ! -f \"$(git rev-parse --git-dir)/refuse\"");
+    repo.git(&["config", "core.hooksPath", &hooks.display().to_string()]);
+    repo.write(".git/refuse", "");
+
+    let output = repo.lab(&["finish", "--continue"]);
+
+    assert!(!output.status.success());
+    assert!(Repo::text(&output).contains("git lab finish --continue"), "{}", Repo::text(&output));
+
+    // Nothing was rolled back: the resolution is still there, to be continued
+    assert!(repo.exists(FINISH_STATE));
+    assert!(repo.exists(".git/MERGE_HEAD"));
+    assert_eq!(repo.read("a.txt"), "both\n");
+    assert!(repo.git_ok(&["rev-parse", "--verify", "feature/conflict"]));
+
+    std::fs::remove_file(repo.path.join(".git/refuse")).unwrap();
+
+    repo.lab_ok(&["finish", "--continue"]);
+
+    assert!(!repo.exists(FINISH_STATE));
+    assert!(!repo.git_ok(&["rev-parse", "--verify", "feature/conflict"]));
+    assert_eq!(repo.git(&["show", "develop:a.txt"]), "both");
+
+}
+
+#[test]
+fn abort_still_works_after_a_failed_continue () {
+
+    let repo = conflicted("abort-after-continue");
+
+    let develop = repo.git(&["rev-parse", "develop"]);
+
+    // No identity: the merge commit can't be made
+    repo.git(&["config", "--unset", "user.email"]);
+    repo.git(&["config", "user.useConfigOnly", "true"]);
+
+    assert!(!repo.lab(&["finish", "--continue"]).status.success());
+    assert!(repo.exists(FINISH_STATE));
+
+    repo.lab_ok(&["finish", "--abort"]);
+
+    assert!(!repo.exists(FINISH_STATE));
+    assert_eq!(repo.git(&["rev-parse", "develop"]), develop);
+    assert_eq!(repo.git(&["branch", "--show-current"]), "feature/conflict");
+
+}
+
+///
+/// Finishes the conflicting feature with uncommitted work: it
+/// stops on conflicts, keeping the work stashed.
+///
+fn conflicted_with_work (name: &str) -> Repo {
+
+    let repo = conflicting(name);
+
+    repo.write("b.txt", "work in progress\n");
+
+    let output = repo.lab(&["feature", "finish", "conflict", "--autostash"]);
+
+    assert!(!output.status.success());
+    assert!(Repo::text(&output).contains("kept stashed"), "{}", Repo::text(&output));
+
+    // Not popped into the conflicted merge
+    assert_eq!(repo.read("b.txt"), "b\n");
+    assert_eq!(repo.git(&["stash", "list", "--format=%s"]), "On feature/conflict: lab-flow autostash");
+    assert!(repo.read(FINISH_STATE).contains(&format!("stash={}", repo.git(&["rev-parse", "refs/stash"]))));
+
+    repo
+
+}
+
+#[test]
+fn autostash_comes_back_with_continue () {
+
+    let repo = conflicted_with_work("autostash-continue");
+
+    repo.write("a.txt", "both\n");
+    repo.git(&["add", "a.txt"]);
+
+    repo.lab_ok(&["finish", "--continue"]);
+
+    assert_eq!(repo.git(&["branch", "--show-current"]), "develop");
+    assert_eq!(repo.read("b.txt"), "work in progress\n");
+    assert_eq!(repo.git(&["stash", "list"]), "");
+
+}
+
+#[test]
+fn autostash_comes_back_with_abort () {
+
+    let repo = conflicted_with_work("autostash-abort");
+
+    // Another stash on top of it
+    repo.git(&["stash", "store", "-m", "mine", "develop"]);
+
+    repo.lab_ok(&["finish", "--abort"]);
+
+    assert_eq!(repo.git(&["branch", "--show-current"]), "feature/conflict");
+    assert_eq!(repo.read("b.txt"), "work in progress\n");
+    assert_eq!(repo.git(&["stash", "list", "--format=%gs"]), "mine");
+
+}
+
+#[test]
+fn autostash_comes_back_after_a_finish () {
+
+    let repo = Repo::new("autostash-finish");
+
+    repo.git(&["checkout", "-q", "develop"]);
+    repo.commit("b.txt", "b\n", "chore: add b");
+
+    repo.lab_ok(&["feature", "start", "clean"]);
+    repo.commit("a.txt", "feature\n", "feat: add a");
+    repo.write("b.txt", "work in progress\n");
+
+    repo.lab_ok(&["feature", "finish", "clean", "--autostash"]);
+
+    assert_eq!(repo.git(&["branch", "--show-current"]), "develop");
+    assert_eq!(repo.read("b.txt"), "work in progress\n");
+    assert_eq!(repo.git(&["stash", "list"]), "");
+
+}
+
+///
+/// A `chore` type merged locally, with its branch started (and
+/// pushed) on a repository with a remote.
+///
+fn started_chore (name: &str) -> Repo {
+
+    let repo = Repo::with_remote(name);
+
+    repo.git(&["config", "lab.flow.type.chore.prefix", "chore/"]);
+    repo.git(&["config", "lab.flow.type.chore.mergerequest", "false"]);
+
+    repo.lab_ok(&["chore", "start", "tidy"]);
+
+    assert!(repo.git_ok(&["rev-parse", "--verify", "origin/chore/tidy"]));
+
+    repo
+
+}
+
+#[test]
+fn remote_branch_is_kept_while_its_work_is_only_there () {
+
+    let repo = started_chore("remote-kept");
+
+    repo.commit("a.txt", "tidy\n", "chore: tidy");
+    repo.git(&["push", "-q", "origin", "chore/tidy"]);
+
+    let output = repo.lab_ok(&["chore", "finish", "tidy"]);
+
+    assert!(output.contains("was kept on the remote"), "{}", output);
+    assert!(repo.git_ok(&["ls-remote", "--exit-code", "origin", "refs/heads/chore/tidy"]));
+
+}
+
+#[test]
+fn remote_branch_is_removed_when_its_work_is_on_the_remote_targets () {
+
+    let repo = started_chore("remote-removed");
+
+    // Only the start commit was pushed, and origin/develop has it
+    repo.commit("a.txt", "tidy\n", "chore: tidy");
+
+    repo.lab_ok(&["chore", "finish", "tidy"]);
+
+    assert!(!repo.git_ok(&["ls-remote", "--exit-code", "origin", "refs/heads/chore/tidy"]));
+
+}
+
+#[test]
+fn changelog_is_dated_today () {
+
+    let repo = Repo::new("changelog-date");
+
+    repo.lab_ok(&["release", "start", "1.0.0"]);
+    repo.commit("a.txt", "a", "feat: a");
+    repo.lab_ok(&["release", "finish", "1.0.0"]);
+
+    let today = std::process::Command::new("date").arg("+%F").output().unwrap();
+
+    assert!(
+        repo.read("CHANGELOG.md").contains(&format!("\n## 1.0.0 ({})\n", String::from_utf8_lossy(&today.stdout).trim())),
+        "{}",
+        repo.read("CHANGELOG.md")
+    );
+
+}
@@ -0,0 +1,44 @@
+mod common;
+
+use common::Repo;
+
+#[test]
+fn hooks_run_quietly () {
+
+    let repo = Repo::new("hooks-quiet");
+
+    repo.lab_ok(&["hooks", "install"]);
+    repo.lab_ok(&["feature", "start", "ABC-1-login"]);
+
+    repo.write("login.txt", "form");
+    repo.git(&["add", "login.txt"]);
+
+    let output = repo.git_output(&["commit", "-m", "add login form"]);
+    let text = Repo::text(&output);
+
+    assert!(output.status.success(), "{}", text);
+    assert!(!text.contains("Lab Flow"), "{}", text);
+    assert!(!text.contains("Done!"), "{}", text);
+    assert_eq!(repo.git(&["log", "-1", "--format=%s"]), "ABC-1 add login form");
+
+}
+
+#[test]
+fn hooks_still_tell_why_they_refuse () {
+
+    let repo = Repo::new("hooks-refuse");
+
+    repo.lab_ok(&["hooks", "install"]);
+    repo.git(&["checkout", "-q", "develop"]);
+
+    repo.write("a.txt", "a");
+    repo.git(&["add", "a.txt"]);
+
+    let output = repo.git_output(&["commit", "-m", "straight to develop"]);
+    let text = Repo::text(&output);
+
+    assert!(!output.status.success(), "{}", text);
+    assert!(text.contains("Commits straight to develop are not allowed"), "{}", text);
+    assert!(!text.contains("Lab Flow"), "{}", text);
+
+}
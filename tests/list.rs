@@ -0,0 +1,19 @@
+mod common;
+
+use common::Repo;
+
+#[test]
+fn last_commit_is_told_by_its_author () {
+
+    let repo = Repo::new("list-author");
+
+    repo.lab_ok(&["feature", "start", "login"]);
+    repo.write("login.txt", "form");
+    repo.git(&["add", "login.txt"]);
+    repo.git(&["commit", "-q", "-m", "feat: login", "--author", "Writer <writer@acme.com>", "--date", "2025-03-04T05:06:07+00:00"]);
+
+    let list = repo.lab_ok(&["feature", "list", "--format", "json"]);
+
+    assert!(list.contains(r#""last_commit_date":"2025-03-04T05:06:07+00:00","author":"Writer","author_email":"writer@acme.com""#), "{}", list);
+
+}
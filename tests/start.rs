@@ -0,0 +1,52 @@
+mod common;
+
+use common::Repo;
+
+#[test]
+fn releases_are_named_after_the_next_version () {
+
+    let repo = Repo::new("bump");
+
+    repo.git(&["tag", "-a", "1.2.0+build.7", "-m", "1.2.0", "main"]);
+
+    repo.lab_ok(&["release", "start", "--bump", "minor"]);
+
+    assert_eq!(repo.git(&["branch", "--show-current"]), "release/1.3.0");
+
+}
+
+#[test]
+fn bump_and_a_name_are_refused_together () {
+
+    let repo = Repo::new("bump-and-name");
+
+    let output = repo.lab(&["release", "start", "2.0.0", "--bump", "minor"]);
+
+    assert!(Repo::text(&output).contains("not both"), "{}", Repo::text(&output));
+    assert!(!repo.git_ok(&["rev-parse", "--verify", "release/2.0.0"]));
+    assert!(!repo.git_ok(&["rev-parse", "--verify", "release/0.1.0"]));
+
+}
+
+#[test]
+fn failed_version_bump_leaves_no_branch () {
+
+    let repo = Repo::new("failed-bump");
+
+    repo.git(&["checkout", "develop"]);
+    repo.write("VERSION", "1.0.0\n");
+    repo.git(&["add", "VERSION"]);
+    repo.commit("Cargo.toml", "[package]\nname = \"app\"\n", "chore: version files");
+
+    repo.git(&["config", "--local", "--add", "lab.flow.version.file", "VERSION"]);
+    repo.git(&["config", "--local", "--add", "lab.flow.version.file", "Cargo.toml"]);
+
+    let output = repo.lab(&["release", "start", "1.1.0"]);
+
+    assert!(Repo::text(&output).contains("No version found in Cargo.toml"), "{}", Repo::text(&output));
+    assert!(!repo.git_ok(&["rev-parse", "--verify", "release/1.1.0"]));
+    assert_eq!(repo.git(&["branch", "--show-current"]), "develop");
+    assert_eq!(repo.read("VERSION"), "1.0.0\n");
+    assert_eq!(repo.git(&["status", "--porcelain"]), "");
+
+}